@@ -6,14 +6,11 @@ use winit::event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta};
 use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::{CursorGrabMode, Window};
 
-use array::array;
+use engine::{Clock, Scene, Tiled};
 use matrix::{Matrix, Vector};
-use render::light::Light;
 
 use crate::args::Args;
 use crate::buffer::PixelsBuffer;
-use crate::scene::Scene;
-use crate::tiled::Tiled;
 
 #[derive(Debug, PartialEq)]
 enum State {
@@ -24,12 +21,11 @@ enum State {
 
 pub struct App {
 	args: Args,
-	last_frame: time::Instant,
+	clock: Clock,
 	frame: PixelsBuffer<'static>,
 	movement: Vector<f32, 3>,
 	orientation: Vector<f32, 2>,
 	state: State,
-	fov: f32,
 	scene: Scene,
 	window: Arc<Window>,
 	projection: Matrix<f32, 4, 4>,
@@ -57,18 +53,19 @@ impl App {
 
 		window.request_redraw();
 
+		let (scene, tiled) = args.load(buffer_width, buffer_height).unwrap();
+
 		let mut app = App {
 			args: args.clone(),
 			frame,
 			window,
-			fov: 60.0,
-			last_frame: time::Instant::now(),
+			clock: Clock::new(args.timestep.map(time::Duration::from_secs_f32)),
 			movement: Vector::zero(),
 			orientation: Vector::zero(),
 			state: State::Initial,
-			scene: Scene::new(&args.scene),
+			scene,
 			projection: Matrix::identity(),
-			tiled: Tiled::new(args.threads, buffer_width, buffer_height),
+			tiled,
 		};
 
 		app.ungrab();
@@ -79,7 +76,7 @@ impl App {
 	pub fn update_projection(&mut self) {
 		let size = self.window.inner_size();
 		let aspect_ratio = size.width as f32 / size.height as f32;
-		self.projection = transform::perspective(aspect_ratio, self.fov.to_radians());
+		self.projection = self.scene.camera.projection(aspect_ratio);
 	}
 
 	pub fn grab(&mut self) {
@@ -116,7 +113,8 @@ impl App {
 			MouseScrollDelta::LineDelta(..) => {}
 
 			MouseScrollDelta::PixelDelta(PhysicalPosition { y, .. }) => {
-				self.fov = (self.fov + y as f32 / 10.0).clamp(10.0, 60.0);
+				let fov = self.scene.camera.fov + y as f32 / 10.0;
+				self.scene.camera.fov = fov.clamp(10.0, 60.0);
 				self.update_projection();
 			}
 		}
@@ -150,6 +148,15 @@ impl App {
 
 		match event.physical_key {
 			PhysicalKey::Code(KeyCode::Escape) => self.ungrab(),
+			PhysicalKey::Code(KeyCode::KeyP) if d == 1.0 && !event.repeat => {
+				if let Err(err) = self.screenshot() {
+					log::error!("Screenshot failed: {}", err);
+				}
+			}
+			PhysicalKey::Code(KeyCode::KeyM) if d == 1.0 && !event.repeat => {
+				self.tiled.mode = self.tiled.mode.next();
+				log::info!("Mode {:?}", self.tiled.mode);
+			}
 			PhysicalKey::Code(KeyCode::KeyW) => self.movement[2] = d,
 			PhysicalKey::Code(KeyCode::KeyA) => self.movement[0] = -d,
 			PhysicalKey::Code(KeyCode::KeyS) => self.movement[2] = -d,
@@ -160,27 +167,47 @@ impl App {
 		}
 	}
 
+	pub fn screenshot(&self) -> anyhow::Result<()> {
+		let timestamp = (time::SystemTime::now())
+			.duration_since(time::UNIX_EPOCH)
+			.unwrap()
+			.as_millis();
+
+		let name = format!("screenshot-{}", timestamp);
+		log::info!("Save {}.png", name);
+		self.frame.save(&format!("{}.png", name))?;
+
+		let mut sidecar = toml::Table::new();
+		sidecar.insert("scene".into(), self.args.scene.clone().into());
+		sidecar.insert(
+			"camera".into(),
+			engine::scene::write_camera(&self.scene.camera).into(),
+		);
+
+		std::fs::write(format!("{}.toml", name), toml::to_string(&sidecar)?)?;
+		Ok(())
+	}
+
 	pub fn update(&mut self) {
 		let now = time::Instant::now();
-		let dt = now - self.last_frame;
-		self.last_frame = now;
-		self.scene.update(dt, self.movement, self.orientation);
-		self.orientation = Vector::zero();
 
-		// TODO
+		for tick in self.clock.tick() {
+			self.scene.update(tick, self.movement, self.orientation);
+			self.orientation = Vector::zero();
+		}
+
 		if self.args.camera_light {
-			self.scene.lights = vec![Light {
-				diffuse_color: array![1.0; 3],
-				specular_color: array![0.5; 3],
-				position: self.scene.camera.position,
-			}];
+			self.scene.camera_light();
 		}
 
-		self.tiled
-			.draw(&mut self.frame, &self.scene, self.projection);
+		let timings = (self.tiled).draw(&mut self.frame, &self.scene, self.projection);
 
 		if self.args.debug {
-			log::info!("frame: {:?}", now.elapsed());
+			log::info!(
+				"frame: {:?} occluded: {:?}",
+				now.elapsed(),
+				timings.occluded
+			);
 		}
 
 		self.window.pre_present_notify();
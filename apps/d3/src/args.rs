@@ -1,3 +1,6 @@
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use engine::{Mode, Samples, Scene, Shading, Tiled};
+
 #[derive(clap::Parser, Debug, Clone)]
 pub struct Args {
 	#[arg(long, default_value_t = 500)]
@@ -15,6 +18,18 @@ pub struct Args {
 	#[arg(long)]
 	pub scene: String,
 
+	#[arg(long)]
+	pub camera: Option<String>,
+
+	#[arg(long)]
+	pub near: Option<f32>,
+
+	#[arg(long)]
+	pub far: Option<f32>,
+
+	#[arg(long, default_value_t = false)]
+	pub reversed_z: bool,
+
 	#[arg(long, default_value_t = false)]
 	pub debug: bool,
 
@@ -23,4 +38,90 @@ pub struct Args {
 
 	#[arg(long, default_value_t = 8)]
 	pub threads: usize,
+
+	#[arg(
+		long,
+		default_value = "1",
+		value_parser = PossibleValuesParser::new(Samples::NAMES)
+			.map(|s| s.parse::<Samples>().unwrap()),
+	)]
+	pub samples: Samples,
+
+	#[arg(
+		long,
+		default_value = "filled",
+		value_parser = PossibleValuesParser::new(Mode::NAMES).map(|s| s.parse::<Mode>().unwrap()),
+	)]
+	pub mode: Mode,
+
+	#[arg(
+		long,
+		value_parser = PossibleValuesParser::new(Shading::NAMES)
+			.map(|s| s.parse::<Shading>().unwrap()),
+	)]
+	pub shading: Option<Shading>,
+
+	#[arg(long, default_value_t = false)]
+	pub scalar: bool,
+
+	#[arg(long)]
+	pub timestep: Option<f32>,
+
+	#[arg(long)]
+	pub output: Option<String>,
+
+	#[arg(long, default_value_t = 1)]
+	pub frames: usize,
+
+	#[arg(long)]
+	pub hdr: Option<String>,
+
+	#[arg(long)]
+	pub depth: Option<String>,
+
+	#[arg(long)]
+	pub normals: Option<String>,
+
+	#[arg(long)]
+	pub export: Option<String>,
+
+	#[arg(long)]
+	pub bench: Option<usize>,
+
+	#[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+	pub fps: u32,
+
+	#[arg(long, default_value_t = 5.0)]
+	pub duration: f32,
+
+	#[arg(long, default_value_t = false)]
+	pub turntable: bool,
+}
+
+impl Args {
+	/// Loads the scene with the camera overrides, and a renderer for a `width` by `height` buffer.
+	pub fn load(&self, width: usize, height: usize) -> anyhow::Result<(Scene, Tiled)> {
+		let mut scene = Scene::load(&self.scene)?;
+
+		if let Some(path) = &self.camera {
+			scene.load_camera(path)?;
+		}
+
+		if let Some(near) = self.near {
+			scene.camera.near = near;
+		}
+
+		if self.far.is_some() {
+			scene.camera.far = self.far;
+		}
+
+		scene.camera.reversed_z |= self.reversed_z;
+
+		let mut tiled = Tiled::new(self.threads, width, height, self.samples);
+		tiled.mode = self.mode;
+		tiled.shading = self.shading.unwrap_or(scene.shading);
+		tiled.simd = !self.scalar;
+
+		Ok((scene, tiled))
+	}
 }
@@ -0,0 +1,66 @@
+use std::time;
+
+use engine::Timings;
+use serde_json::json;
+
+use crate::args::Args;
+use crate::headless::{FRAME_TIME, Headless};
+
+pub fn run(args: &Args, frames: usize) -> anyhow::Result<()> {
+	let mut headless = Headless::new(args, FRAME_TIME)?;
+	let mut samples: Vec<(Timings, time::Duration)> = Vec::with_capacity(frames);
+
+	for frame in 0..frames {
+		if frame > 0 {
+			headless.advance(FRAME_TIME);
+		}
+
+		let now = time::Instant::now();
+		let timings = headless.draw();
+		samples.push((timings, now.elapsed()));
+	}
+
+	let stage = |f: fn(&(Timings, time::Duration)) -> time::Duration| {
+		let mut durations: Vec<_> = samples.iter().map(f).collect();
+		durations.sort();
+		summary(&durations)
+	};
+
+	let report = json!({
+		"scene": args.scene,
+		"frames": frames,
+		"width": args.width / args.scale,
+		"height": args.height / args.scale,
+		"threads": args.threads,
+		"unit": "ms",
+		"stages": {
+			"vertex": stage(|(timings, _)| timings.vertex),
+			"setup": stage(|(timings, _)| timings.setup),
+			"rasterize": stage(|(timings, _)| timings.rasterize),
+			"compose": stage(|(timings, _)| timings.compose),
+			"total": stage(|(_, total)| *total),
+		},
+		"occluded": {
+			"triangles": samples.iter().map(|(timings, _)| timings.occluded.triangles).sum::<usize>() / frames.max(1),
+			"blocks": samples.iter().map(|(timings, _)| timings.occluded.blocks).sum::<usize>() / frames.max(1),
+		},
+	});
+
+	println!("{}", serde_json::to_string_pretty(&report)?);
+	Ok(())
+}
+
+fn summary(sorted: &[time::Duration]) -> serde_json::Value {
+	let ms = |d: time::Duration| d.as_secs_f64() * 1000.0;
+
+	json!({
+		"min": sorted.first().copied().map(ms),
+		"median": percentile(sorted, 0.5).map(ms),
+		"p99": percentile(sorted, 0.99).map(ms),
+	})
+}
+
+fn percentile(sorted: &[time::Duration], p: f64) -> Option<time::Duration> {
+	let rank = (p * sorted.len() as f64).ceil() as usize;
+	sorted.get(rank.saturating_sub(1)).copied()
+}
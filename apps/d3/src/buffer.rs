@@ -1,12 +1,5 @@
 use pixels::Pixels;
-
-pub trait Buffer<C> {
-	#[allow(dead_code)]
-	fn clear(&mut self, color: C);
-	fn put(&mut self, x: usize, y: usize, color: C);
-	fn height(&self) -> usize;
-	fn width(&self) -> usize;
-}
+use render::buffer::{Buffer, Target};
 
 pub struct PixelsBuffer<'a> {
 	buffer: Pixels<'a>,
@@ -26,25 +19,32 @@ impl PixelsBuffer<'_> {
 	pub fn render(&self) {
 		self.buffer.render().unwrap();
 	}
-}
 
-impl Buffer<[u8; 4]> for &mut PixelsBuffer<'_> {
-	fn clear(&mut self, color: [u8; 4]) {
-		let frame = self.buffer.frame_mut();
-		frame.copy_from_slice(&color.repeat(frame.len() / 4));
+	pub fn save(&self, path: &str) -> anyhow::Result<()> {
+		let frame = self.buffer.frame().to_vec();
+		let image =
+			image::RgbaImage::from_raw(self.width as u32, self.height as u32, frame).unwrap();
+		image.save(path)?;
+		Ok(())
 	}
+}
 
-	fn put(&mut self, x: usize, y: usize, color: [u8; 4]) {
-		let frame = self.buffer.frame_mut();
-		let i = x * 4 + y * self.width * 4;
-		frame[i..i + 4].copy_from_slice(&color)
+impl Buffer for &mut PixelsBuffer<'_> {
+	type Unit = [u8; 4];
+
+	fn width(&self) -> usize {
+		self.width
 	}
 
 	fn height(&self) -> usize {
 		self.height
 	}
+}
 
-	fn width(&self) -> usize {
-		self.width
+impl Target for &mut PixelsBuffer<'_> {
+	fn put(&mut self, x: usize, y: usize, color: Self::Unit) {
+		let frame = self.buffer.frame_mut();
+		let i = x * 4 + y * self.width * 4;
+		frame[i..i + 4].copy_from_slice(&color)
 	}
 }
@@ -0,0 +1,69 @@
+use std::{f32::consts::TAU, path::Path, time};
+
+use engine::Camera;
+use matrix::{Vector, vector};
+
+use crate::args::Args;
+use crate::headless::Headless;
+
+struct Turntable {
+	center: Vector<f32, 3>,
+	radius: f32,
+	height: f32,
+	angle: f32,
+}
+
+impl Turntable {
+	fn new(camera: &Camera, center: Vector<f32, 3>) -> Self {
+		let offset = camera.position - center;
+
+		Turntable {
+			center,
+			radius: vector![offset[0], offset[2]].magnitude(),
+			height: offset[1],
+			angle: offset[2].atan2(offset[0]),
+		}
+	}
+
+	fn update(&self, camera: &mut Camera, t: f32) {
+		let (sin, cos) = (self.angle + TAU * t).sin_cos();
+		let position = self.center + vector![self.radius * cos, self.height, self.radius * sin];
+		camera.look_at(position, self.center);
+	}
+}
+
+pub fn run(args: &Args, directory: &str) -> anyhow::Result<()> {
+	std::fs::create_dir_all(directory)?;
+
+	let frames = (args.duration * args.fps as f32).round() as usize;
+	let dt = time::Duration::from_secs_f64(1.0 / args.fps as f64);
+	let mut headless = Headless::new(args, dt)?;
+
+	let turntable = args
+		.turntable
+		.then(|| Turntable::new(&headless.scene.camera, headless.scene.center()));
+
+	log::info!("Export {} frames at {} fps", frames, args.fps);
+
+	for frame in 0..frames {
+		if frame > 0 {
+			headless.advance(dt);
+		}
+
+		if let Some(turntable) = &turntable {
+			turntable.update(&mut headless.scene.camera, frame as f32 / frames as f32);
+		}
+
+		let now = time::Instant::now();
+		headless.draw();
+
+		if args.debug {
+			log::info!("frame {}: {:?}", frame, now.elapsed());
+		}
+
+		let path = Path::new(directory).join(format!("frame-{:05}.png", frame));
+		headless.save(path.to_str().unwrap())?;
+	}
+
+	Ok(())
+}
@@ -0,0 +1,133 @@
+use std::time;
+
+use anyhow::Context;
+use engine::{Clock, Layers, Scene, Tiled, Timings};
+use matrix::{Matrix, Vector};
+use render::buffer::{DepthBuffer, FloatBuffer, ImageBuffer};
+
+use crate::args::Args;
+
+pub const FRAME_TIME: time::Duration = time::Duration::from_nanos(1_000_000_000 / 60);
+
+pub struct Headless {
+	pub scene: Scene,
+	camera_light: bool,
+	clock: Clock,
+	frame: ImageBuffer,
+	hdr: Option<FloatBuffer>,
+	depth: Option<DepthBuffer>,
+	normals: Option<ImageBuffer>,
+	projection: Matrix<f32, 4, 4>,
+	tiled: Tiled,
+}
+
+impl Headless {
+	pub fn new(args: &Args, step: time::Duration) -> anyhow::Result<Self> {
+		let buffer_width = (args.width / args.scale) as usize;
+		let buffer_height = (args.height / args.scale) as usize;
+
+		log::info!("Start headless buffer={:?}", (buffer_width, buffer_height));
+
+		let aspect_ratio = args.width as f32 / args.height as f32;
+		let (scene, tiled) = args.load(buffer_width, buffer_height)?;
+
+		Ok(Headless {
+			camera_light: args.camera_light,
+			clock: Clock::new(Some(step)),
+			frame: ImageBuffer::new(buffer_width, buffer_height),
+			hdr: (args.hdr.as_ref()).map(|_| FloatBuffer::new(buffer_width, buffer_height)),
+			depth: (args.depth.as_ref())
+				.map(|_| DepthBuffer::new(buffer_width, buffer_height, f32::INFINITY)),
+			normals: (args.normals.as_ref()).map(|_| ImageBuffer::new(buffer_width, buffer_height)),
+			projection: scene.camera.projection(aspect_ratio),
+			scene,
+			tiled,
+		})
+	}
+
+	pub fn advance(&mut self, duration: time::Duration) {
+		for tick in self.clock.advance(duration) {
+			self.scene.update(tick, Vector::zero(), Vector::zero());
+		}
+	}
+
+	pub fn draw(&mut self) -> Timings {
+		if self.camera_light {
+			self.scene.camera_light();
+		}
+
+		let layers = Layers {
+			hdr: self.hdr.as_mut(),
+			depth: self.depth.as_mut(),
+			normals: self.normals.as_mut(),
+		};
+
+		self.tiled
+			.draw_layers(&mut self.frame, layers, &self.scene, self.projection)
+	}
+
+	pub fn save(&self, path: &str) -> anyhow::Result<()> {
+		log::info!("Save {}", path);
+		self.frame.save(path).with_context(|| path.to_string())
+	}
+
+	pub fn save_hdr(&self, path: &str) -> anyhow::Result<()> {
+		if let Some(hdr) = &self.hdr {
+			log::info!("Save {}", path);
+			hdr.save(path).with_context(|| path.to_string())?;
+		}
+
+		Ok(())
+	}
+
+	pub fn save_depth(&self, path: &str) -> anyhow::Result<()> {
+		if let Some(depth) = &self.depth {
+			log::info!("Save {}", path);
+			depth.save(path).with_context(|| path.to_string())?;
+		}
+
+		Ok(())
+	}
+
+	pub fn save_normals(&self, path: &str) -> anyhow::Result<()> {
+		if let Some(normals) = &self.normals {
+			log::info!("Save {}", path);
+			normals.save(path).with_context(|| path.to_string())?;
+		}
+
+		Ok(())
+	}
+}
+
+pub fn run(args: &Args, output: &str) -> anyhow::Result<()> {
+	let mut headless = Headless::new(args, FRAME_TIME)?;
+
+	for frame in 0..args.frames {
+		if frame > 0 {
+			headless.advance(FRAME_TIME);
+		}
+
+		let now = time::Instant::now();
+		headless.draw();
+
+		if args.debug {
+			log::info!("frame {}: {:?}", frame, now.elapsed());
+		}
+	}
+
+	headless.save(output)?;
+
+	if let Some(path) = &args.hdr {
+		headless.save_hdr(path)?;
+	}
+
+	if let Some(path) = &args.depth {
+		headless.save_depth(path)?;
+	}
+
+	if let Some(path) = &args.normals {
+		headless.save_normals(path)?;
+	}
+
+	Ok(())
+}
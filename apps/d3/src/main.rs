@@ -1,7 +1,3 @@
-#![feature(coroutines)]
-#![feature(iter_from_coroutine)]
-#![feature(let_chains)]
-
 use clap::Parser;
 use winit::application::ApplicationHandler;
 use winit::dpi::{LogicalPosition, LogicalSize};
@@ -11,11 +7,10 @@ use winit::window::{Fullscreen, Window, WindowId};
 
 mod app;
 mod args;
+mod bench;
 mod buffer;
-mod camera;
-mod object;
-mod scene;
-mod tiled;
+mod export;
+mod headless;
 
 use app::App;
 use args::Args;
@@ -26,10 +21,23 @@ enum State {
 }
 
 fn main() -> anyhow::Result<()> {
-	env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("d3,obj"))
+	env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("d3,engine,obj"))
 		.format_timestamp(None)
 		.init();
 	let args = Args::parse();
+
+	if let Some(output) = &args.output {
+		return headless::run(&args, output);
+	}
+
+	if let Some(directory) = &args.export {
+		return export::run(&args, directory);
+	}
+
+	if let Some(frames) = args.bench {
+		return bench::run(&args, frames);
+	}
+
 	let mut state = State::Starting(args);
 	let event_loop = EventLoop::new()?;
 	event_loop.set_control_flow(ControlFlow::Poll);
@@ -0,0 +1,100 @@
+use matrix::{Matrix, Vector, vector};
+
+use crate::clock::Tick;
+
+/// First-person camera oriented by yaw and pitch, with a vertical field of view, in degrees.
+pub struct Camera {
+	pub speed: f32,
+	pub sensitivity: f32,
+	pub pitch: f32,
+	pub yaw: f32,
+	pub fov: f32,
+	pub near: f32,
+	/// Far clipping distance, or `None` for an infinite projection.
+	pub far: Option<f32>,
+	/// Whether depth is 1 at `near` and decreases with distance, for more precision far away.
+	pub reversed_z: bool,
+	pub position: Vector<f32, 3>,
+	pub target: Vector<f32, 3>,
+	pub up: Vector<f32, 3>,
+	pub view: Matrix<f32, 4, 4>,
+}
+
+impl Camera {
+	pub fn new(position: Vector<f32, 3>, pitch: f32, yaw: f32, fov: f32) -> Self {
+		let mut camera = Camera {
+			speed: 3.5,
+			sensitivity: 12.0,
+			position,
+			pitch,
+			yaw,
+			fov,
+			near: 0.1,
+			far: None,
+			reversed_z: false,
+			target: vector![0.0, 0.0, -1.0],
+			up: vector![0.0, 1.0, 0.0],
+			view: Matrix::identity(),
+		};
+
+		camera.update_matrix();
+		camera
+	}
+
+	/// Perspective projection for a viewport of `aspect_ratio`, clipping at `near` and `far`.
+	///
+	/// Visible depths always lie in `0 <= z <= w`, with the near plane at 0, or at `w` when reversed.
+	pub fn projection(&self, aspect_ratio: f32) -> Matrix<f32, 4, 4> {
+		let fov = self.fov.to_radians();
+
+		match (self.far, self.reversed_z) {
+			(None, false) => transform::perspective_near(aspect_ratio, fov, self.near),
+			(None, true) => transform::perspective_reversed(aspect_ratio, fov, self.near),
+			(Some(far), reversed) => {
+				let m = transform::perspective_near_far(aspect_ratio, fov, self.near, far);
+				let (near, far) = if reversed { (1.0, 0.0) } else { (0.0, 1.0) };
+				m * depth_range(near, far)
+			}
+		}
+	}
+
+	/// Moves along the camera axes and turns by `orientation` (yaw, pitch) over one step.
+	pub fn update(&mut self, tick: Tick, movement: Vector<f32, 3>, orientation: Vector<f32, 2>) {
+		let dt = tick.dt.as_secs_f32();
+		let right = self.target.cross(self.up).normalize();
+		let forward = self.up.cross(right).normalize();
+
+		self.position += right * movement[0] * self.speed * dt;
+		self.position += self.up * movement[1] * self.speed * dt;
+		self.position += forward * movement[2] * self.speed * dt;
+
+		self.yaw += orientation[0] * self.sensitivity * dt;
+		self.pitch -= orientation[1] * self.sensitivity * dt;
+		self.pitch = self.pitch.clamp(-89.0, 89.0);
+
+		self.update_matrix();
+	}
+
+	/// Places the camera at `position` facing `target`.
+	pub fn look_at(&mut self, position: Vector<f32, 3>, target: Vector<f32, 3>) {
+		let direction = (target - position).normalize();
+		self.position = position;
+		self.pitch = direction[1].asin().to_degrees();
+		self.yaw = direction[2].atan2(direction[0]).to_degrees();
+		self.update_matrix();
+	}
+
+	fn update_matrix(&mut self) {
+		let (sin_yaw, cos_yaw) = self.yaw.to_radians().sin_cos();
+		let (sin_pitch, cos_pitch) = self.pitch.to_radians().sin_cos();
+		self.target = vector![cos_yaw * cos_pitch, sin_pitch, sin_yaw * cos_pitch].normalize();
+		self.view = transform::look_to(self.position, self.position + self.target, self.up);
+	}
+}
+
+fn depth_range(near: f32, far: f32) -> Matrix<f32, 4, 4> {
+	let mut m = Matrix::identity();
+	m[(2, 2)] = (far - near) / 2.0;
+	m[(3, 2)] = (far + near) / 2.0;
+	m
+}
@@ -0,0 +1,80 @@
+use std::time;
+
+const MAX_ELAPSED: time::Duration = time::Duration::from_millis(250);
+
+/// One simulation step.
+#[derive(Copy, Clone, Debug)]
+pub struct Tick {
+	pub dt: time::Duration,
+}
+
+/// Simulation clock producing [`Tick`]s of a fixed or variable length.
+pub struct Clock {
+	step: Option<time::Duration>,
+	accumulator: time::Duration,
+	last: time::Instant,
+}
+
+impl Clock {
+	/// Creates a clock with a fixed `step`, or a variable step when `None`.
+	pub fn new(step: Option<time::Duration>) -> Self {
+		Clock {
+			step,
+			accumulator: time::Duration::ZERO,
+			last: time::Instant::now(),
+		}
+	}
+
+	/// Advances by the wall-clock time elapsed since the previous tick.
+	pub fn tick(&mut self) -> impl Iterator<Item = Tick> + '_ {
+		let now = time::Instant::now();
+		let elapsed = (now - self.last).min(MAX_ELAPSED);
+		self.last = now;
+		self.advance(elapsed)
+	}
+
+	/// Advances by `duration`, yielding every whole step that fits in the accumulated time.
+	pub fn advance(&mut self, duration: time::Duration) -> impl Iterator<Item = Tick> + '_ {
+		self.accumulator += duration;
+
+		std::iter::from_fn(move || {
+			let dt = self.step.unwrap_or(self.accumulator);
+
+			if dt.is_zero() || self.accumulator < dt {
+				return None;
+			}
+
+			self.accumulator -= dt;
+			Some(Tick { dt })
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use crate::clock::Clock;
+
+	#[test]
+	fn fixed_step_test() {
+		let mut clock = Clock::new(Some(Duration::from_millis(10)));
+
+		assert_eq!(clock.advance(Duration::from_millis(25)).count(), 2);
+		assert_eq!(clock.advance(Duration::from_millis(4)).count(), 0);
+		assert_eq!(clock.advance(Duration::from_millis(1)).count(), 1);
+
+		for tick in clock.advance(Duration::from_millis(30)) {
+			assert_eq!(tick.dt, Duration::from_millis(10));
+		}
+	}
+
+	#[test]
+	fn variable_step_test() {
+		let mut clock = Clock::new(None);
+		let ticks: Vec<_> = clock.advance(Duration::from_millis(25)).collect();
+		assert_eq!(ticks.len(), 1);
+		assert_eq!(ticks[0].dt, Duration::from_millis(25));
+		assert_eq!(clock.advance(Duration::ZERO).count(), 0);
+	}
+}
@@ -0,0 +1,44 @@
+//! Scene loading, simulation and tiled software rasterization.
+//!
+//! A [`Scene`] is loaded from a TOML description, advanced in time with a
+//! [`Clock`] and drawn by a [`Tiled`] renderer into any
+//! [`render::buffer::Target`]:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use engine::{Clock, Samples, Scene, Tiled};
+//! use matrix::Vector;
+//! use render::buffer::ImageBuffer;
+//!
+//! let mut scene = Scene::load("scenes/dice.toml")?;
+//! let mut clock = Clock::new(Some(Duration::from_secs_f32(1.0 / 60.0)));
+//! let tiled = Tiled::new(8, 320, 240, Samples::Four);
+//! let projection = scene.camera.projection(320.0 / 240.0);
+//! let mut frame = ImageBuffer::new(320, 240);
+//!
+//! for tick in clock.advance(Duration::from_secs(1)) {
+//!     scene.update(tick, Vector::zero(), Vector::zero());
+//! }
+//!
+//! tiled.draw(&mut frame, &scene, projection);
+//! frame.save("dice.png")?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+#![feature(coroutines)]
+#![feature(iter_from_coroutine)]
+#![feature(portable_simd)]
+
+pub mod camera;
+pub mod clock;
+pub mod object;
+pub mod scene;
+pub mod shader;
+pub mod tiled;
+
+pub use camera::Camera;
+pub use clock::{Clock, Tick};
+pub use object::{Cull, Object};
+pub use scene::Scene;
+pub use tiled::{Layers, Mode, Occlusion, Samples, Shaded, Shading, Tiled, Timings};
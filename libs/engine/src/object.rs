@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+
+use matrix::{Matrix, Vector};
+
+use crate::clock::Tick;
+use crate::shader::{self, Shader};
+
+/// A mesh placed in the world, optionally spinning over time.
+pub struct Object {
+	pub mesh: obj::Mesh,
+	pub scale: Vector<f32, 3>,
+	pub orientation: Vector<f32, 3>,
+	pub position: Vector<f32, 3>,
+	pub world_space: Matrix<f32, 4, 4>,
+	pub normal_space: Matrix<f32, 3, 3>,
+	pub update: Option<Update>,
+	pub cull: Cull,
+	pub material_cull: HashMap<String, Cull>,
+	pub shader: Shader,
+}
+
+/// Which triangle faces are discarded; faces drawn from behind are shaded with flipped normals.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Cull {
+	#[default]
+	Back,
+	Front,
+	None,
+}
+
+/// Per-second change applied to an object by [`Object::update`].
+pub struct Update {
+	pub orientation: Vector<f32, 3>,
+}
+
+impl Object {
+	/// Loads the OBJ mesh at `path` and places it with the given transform.
+	pub fn new(
+		path: &str,
+		scale: Vector<f32, 3>,
+		orientation: Vector<f32, 3>,
+		position: Vector<f32, 3>,
+		update: Option<Update>,
+	) -> anyhow::Result<Self> {
+		let mesh = obj::Mesh::new(path).with_context(|| path.to_string())?;
+		let world_space = transform::scale_vector(scale)
+			* transform::rotate_vector(orientation)
+			* transform::translate_vector(position);
+		let normal_space = world_space.sub_matrix(3, 3).unwrap();
+
+		Ok(Object {
+			mesh,
+			scale,
+			orientation,
+			position,
+			normal_space,
+			world_space,
+			update,
+			cull: Cull::default(),
+			material_cull: HashMap::new(),
+			shader: shader::blinn_phong,
+		})
+	}
+
+	/// Culling for triangles using `material`, falling back to the object's mode.
+	pub fn culling(&self, material: Option<&String>) -> Cull {
+		(material.and_then(|name| self.material_cull.get(name))).map_or(self.cull, |cull| *cull)
+	}
+
+	/// Applies one simulation step of the object's [`Update`], if any.
+	pub fn update(&mut self, tick: Tick) {
+		if let Some(update) = &self.update {
+			let dt = tick.dt.as_secs_f32();
+
+			self.orientation += update.orientation * dt;
+			self.world_space = transform::scale_vector(self.scale)
+				* transform::rotate_vector(self.orientation)
+				* transform::translate_vector(self.position);
+			self.normal_space = self.world_space.sub_matrix(3, 3).unwrap();
+		}
+	}
+}
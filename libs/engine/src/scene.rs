@@ -0,0 +1,354 @@
+use anyhow::Context;
+
+use array::{Array, array};
+use matrix::{Vector, vector};
+use render::light::Light;
+
+use crate::camera::Camera;
+use crate::clock::Tick;
+use crate::object;
+use crate::object::{Cull, Object};
+use crate::shader::{self, Shader};
+use crate::tiled::Shading;
+
+/// Objects, lights and camera described by a scene file.
+pub struct Scene {
+	pub objects: Vec<Object>,
+	pub lights: Vec<Light>,
+	pub camera: Camera,
+	pub shading: Shading,
+}
+
+impl Scene {
+	/// Loads a scene from a TOML file with a `[camera]` table, `[[objects]]`, optional `[[lights]]`
+	/// and an optional `shading` of `"forward"` or `"deferred"`.
+	///
+	/// Each object has a `mesh` and optional `scale`, `orientation`, `position`, `update`, `shader`
+	/// and `cull` of `"back"`, `"front"` or `"none"`, which `[objects.materials.<name>]` tables may
+	/// override per material.
+	///
+	/// Mesh paths are resolved relative to the current directory.
+	pub fn load(path: &str) -> anyhow::Result<Self> {
+		log::info!("Load {}", path);
+
+		let table = std::fs::read_to_string(path)
+			.with_context(|| path.to_string())?
+			.parse::<toml::Table>()
+			.with_context(|| path.to_string())?;
+
+		let camera = read_camera(table.get("camera").context("camera")?).context("camera")?;
+		let objects = read_objects(&table).context("objects")?;
+		let lights = read_lights(&table).context("lights")?;
+
+		let shading = if let Some(v) = table.get("shading") {
+			(v.as_str().context("string")?.parse()).context("shading")?
+		} else {
+			Shading::default()
+		};
+
+		Ok(Self {
+			camera,
+			objects,
+			lights,
+			shading,
+		})
+	}
+
+	/// Replaces the camera with the `[camera]` table of another TOML file, such as a screenshot sidecar.
+	pub fn load_camera(&mut self, path: &str) -> anyhow::Result<()> {
+		let table = std::fs::read_to_string(path)
+			.with_context(|| path.to_string())?
+			.parse::<toml::Table>()
+			.with_context(|| path.to_string())?;
+
+		self.camera = read_camera(table.get("camera").context("camera")?).context("camera")?;
+		Ok(())
+	}
+
+	/// Advances objects by one simulation step and moves the camera by the given input.
+	pub fn update(&mut self, tick: Tick, movement: Vector<f32, 3>, orientation: Vector<f32, 2>) {
+		if movement != vector![0.0; 3] || orientation != vector![0.0; 2] {
+			self.camera.update(tick, movement, orientation);
+		}
+
+		for object in self.objects.iter_mut() {
+			object.update(tick);
+		}
+	}
+
+	/// Center of the world-space bounding box of every object.
+	pub fn center(&self) -> Vector<f32, 3> {
+		let (min, max) = (self.objects.iter())
+			.flat_map(|object| {
+				(object.mesh.positions.iter()).map(|v| (v.v4() * object.world_space).v3())
+			})
+			.fold(
+				(vector![f32::INFINITY; 3], vector![f32::NEG_INFINITY; 3]),
+				|(min, max), v| {
+					(
+						Vector::from_fn(|_, i| min[i].min(v[i])),
+						Vector::from_fn(|_, i| max[i].max(v[i])),
+					)
+				},
+			);
+
+		(min + max) / 2.0
+	}
+
+	/// Replaces the scene lights with a single light at the camera position.
+	pub fn camera_light(&mut self) {
+		self.lights = vec![Light {
+			diffuse_color: array![1.0; 3],
+			specular_color: array![0.5; 3],
+			position: self.camera.position,
+		}];
+	}
+}
+
+fn read_lights(table: &toml::Table) -> anyhow::Result<Vec<Light>> {
+	(table.get("lights").and_then(|v| v.as_array()))
+		.map(|vs| vs.iter().map(read_light).collect())
+		.unwrap_or(Ok(vec![]))
+}
+
+fn read_objects(table: &toml::Table) -> anyhow::Result<Vec<Object>> {
+	table
+		.get("objects")
+		.and_then(|v| v.as_array())
+		.context("array")?
+		.iter()
+		.map(read_object)
+		.collect()
+}
+
+fn read_object(table: &toml::Value) -> anyhow::Result<Object> {
+	let path = table.get("mesh").and_then(|v| v.as_str()).context("mesh")?;
+
+	let scale = if let Some(v) = table.get("scale") {
+		read_vector(v).context("scale")?
+	} else {
+		vector![1.0; 3]
+	};
+
+	let orientation = if let Some(v) = table.get("orientation") {
+		read_vector(v).context("orientation")?
+	} else {
+		vector![0.0; 3]
+	};
+
+	let position = if let Some(v) = table.get("position") {
+		read_vector(v).context("position")?
+	} else {
+		vector![0.0; 3]
+	};
+
+	let update = if let Some(table) = table.get("update") {
+		let orientation = if let Some(v) = table.get("orientation") {
+			read_vector(v).context("update.orientation")?
+		} else {
+			vector![0.0; 3]
+		};
+
+		Some(object::Update { orientation })
+	} else {
+		None
+	};
+
+	let mut object = Object::new(path, scale, orientation, position, update)?;
+
+	if let Some(v) = table.get("cull") {
+		object.cull = read_cull(v).context("cull")?;
+	}
+
+	if let Some(v) = table.get("shader") {
+		object.shader = read_shader(v).context("shader")?;
+	}
+
+	if let Some(materials) = table.get("materials") {
+		let materials = materials.as_table().context("materials")?;
+
+		for (name, table) in materials.iter() {
+			if let Some(v) = table.get("cull") {
+				let cull = read_cull(v).with_context(|| format!("materials.{}.cull", name))?;
+				object.material_cull.insert(name.clone(), cull);
+			}
+		}
+	}
+
+	Ok(object)
+}
+
+fn read_cull(value: &toml::Value) -> anyhow::Result<Cull> {
+	match value.as_str().context("string")? {
+		"back" => Ok(Cull::Back),
+		"front" => Ok(Cull::Front),
+		"none" => Ok(Cull::None),
+		other => anyhow::bail!("unknown cull mode {}", other),
+	}
+}
+
+fn read_shader(value: &toml::Value) -> anyhow::Result<Shader> {
+	let name = value.as_str().context("string")?;
+	shader::find(name).with_context(|| format!("unknown shader {}", name))
+}
+
+fn read_vector(value: &toml::Value) -> anyhow::Result<Vector<f32, 3>> {
+	read_triplet(value, |x, y, z| vector![x, y, z])
+}
+
+fn read_array(value: &toml::Value) -> anyhow::Result<Array<f32, 3>> {
+	read_triplet(value, |a, b, c| array![a, b, c])
+}
+
+fn read_triplet<T>(value: &toml::Value, f: impl Fn(f32, f32, f32) -> T) -> anyhow::Result<T> {
+	let vs = value.as_array().context("array")?;
+	let cell = |i: usize| {
+		(vs.get(i).and_then(|v| v.as_float()))
+			.map(|v| v as f32)
+			.context("float")
+	};
+
+	Ok(f(cell(0)?, cell(1)?, cell(2)?))
+}
+
+fn read_float(value: &toml::Value) -> anyhow::Result<f32> {
+	value.as_float().map(|v| v as f32).context("float")
+}
+
+fn read_camera(table: &toml::Value) -> anyhow::Result<Camera> {
+	let position = read_vector(table.get("position").context("position")?).context("position")?;
+
+	let pitch = if let Some(v) = table.get("pitch") {
+		read_float(v).context("pitch")?
+	} else {
+		0.0
+	};
+
+	let yaw = if let Some(v) = table.get("yaw") {
+		read_float(v).context("yaw")?
+	} else {
+		-90.0
+	};
+
+	let fov = if let Some(v) = table.get("fov") {
+		read_float(v).context("fov")?
+	} else {
+		60.0
+	};
+
+	let mut camera = Camera::new(position, pitch, yaw, fov);
+
+	if let Some(v) = table.get("near") {
+		camera.near = read_float(v).context("near")?;
+	}
+
+	if let Some(v) = table.get("far") {
+		camera.far = Some(read_float(v).context("far")?);
+	}
+
+	if let Some(v) = table.get("reversed_z") {
+		camera.reversed_z = v.as_bool().context("reversed_z")?;
+	}
+
+	Ok(camera)
+}
+
+/// Writes `camera` as a table readable from a scene's `[camera]` section.
+pub fn write_camera(camera: &Camera) -> toml::Table {
+	let position = (0..3).map(|i| (camera.position[i] as f64).into()).collect();
+
+	let mut table = toml::Table::new();
+	table.insert("position".into(), toml::Value::Array(position));
+	table.insert("pitch".into(), (camera.pitch as f64).into());
+	table.insert("yaw".into(), (camera.yaw as f64).into());
+	table.insert("fov".into(), (camera.fov as f64).into());
+	table.insert("near".into(), (camera.near as f64).into());
+
+	if let Some(far) = camera.far {
+		table.insert("far".into(), (far as f64).into());
+	}
+
+	table.insert("reversed_z".into(), camera.reversed_z.into());
+	table
+}
+
+fn read_light(table: &toml::Value) -> anyhow::Result<Light> {
+	let position = read_vector(table.get("position").context("position")?).context("position")?;
+
+	let diffuse_color = if let Some(v) = table.get("diffuse_color") {
+		read_array(v).context("diffuse_color")?
+	} else {
+		array![1.0; 3]
+	};
+
+	let specular_color = if let Some(v) = table.get("specular_color") {
+		read_array(v).context("specular_color")?
+	} else {
+		array![0.0; 3]
+	};
+
+	// let object = table
+	// 	.get("mesh")
+	// 	.and_then(|v| v.as_str())
+	// 	.map(|path| Object::new(path, vector![1.0; 3], vector![0.0; 3], position, None));
+
+	Ok(Light {
+		diffuse_color,
+		specular_color,
+		position,
+		// object,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::read_objects;
+	use crate::object::Cull;
+
+	const MESH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../objects/alpha/alpha.obj");
+
+	fn parse(materials: &str) -> anyhow::Result<Vec<crate::Object>> {
+		let table = format!(
+			"[[objects]]\nmesh = \"{}\"\ncull = \"front\"\n{}",
+			MESH, materials
+		)
+		.parse::<toml::Table>()?;
+		read_objects(&table)
+	}
+
+	#[test]
+	fn material_cull_test() {
+		let objects = parse(
+			r#"
+			[objects.materials.Material]
+			cull = "none"
+
+			[objects.materials."Material.001"]
+			cull = "back"
+
+			[objects.materials."Material.002"]
+			cull = "front"
+			"#,
+		)
+		.unwrap();
+		let object = &objects[0];
+
+		assert_eq!(object.cull, Cull::Front);
+		assert_eq!(object.culling(Some(&"Material".to_string())), Cull::None);
+		assert_eq!(
+			object.culling(Some(&"Material.001".to_string())),
+			Cull::Back
+		);
+		assert_eq!(
+			object.culling(Some(&"Material.002".to_string())),
+			Cull::Front
+		);
+		assert_eq!(object.culling(Some(&"other".to_string())), Cull::Front);
+		assert_eq!(object.culling(None), Cull::Front);
+
+		let error = parse("[objects.materials.Material]\ncull = \"sideways\"")
+			.err()
+			.unwrap();
+		assert!(format!("{:#}", error).contains("materials.Material.cull"));
+	}
+}
@@ -0,0 +1,116 @@
+use array::{Array, array};
+use matrix::Vector;
+use render::light::{self, Light};
+
+/// Shades one fragment of an object, returning its color and alpha, or `None` to discard it.
+pub type Shader = fn(&Input) -> Option<(Array<f32, 3>, f32)>;
+
+/// Built-in shaders by the name objects select them with in a scene file.
+pub const SHADERS: [(&str, Shader); 5] = [
+	("blinn_phong", blinn_phong),
+	("unlit", unlit),
+	("normals", normals),
+	("toon", toon),
+	("depth", depth),
+];
+
+const TOON_BANDS: f32 = 3.0;
+
+const DEPTH_HALF: f32 = 5.0;
+
+/// Interpolated attributes of one fragment and the frame it is drawn in.
+pub struct Input<'a> {
+	/// World-space position.
+	pub position: Vector<f32, 3>,
+	/// World-space unit normal, facing the viewer on back faces.
+	pub normal: Option<Vector<f32, 3>>,
+	pub uv: Option<Vector<f32, 2>>,
+	pub material: Option<&'a obj::Material>,
+	pub camera: Vector<f32, 3>,
+	pub lights: &'a [Light],
+}
+
+fn missing() -> Option<(Array<f32, 3>, f32)> {
+	Some((array![1.0, 0.0, 1.0], 1.0))
+}
+
+/// The built-in shader called `name`.
+pub fn find(name: &str) -> Option<Shader> {
+	(SHADERS.iter())
+		.find(|(shader, _)| *shader == name)
+		.map(|(_, shader)| *shader)
+}
+
+/// Diffuse and specular lighting from every light.
+pub fn blinn_phong(input: &Input) -> Option<(Array<f32, 3>, f32)> {
+	let (Some(material), Some(normal)) = (input.material, input.normal) else {
+		return missing();
+	};
+
+	light::blinn_phong(
+		input.position,
+		normal,
+		input.uv,
+		input.camera,
+		input.lights,
+		material,
+	)
+}
+
+/// Diffuse color of the material without lighting.
+pub fn unlit(input: &Input) -> Option<(Array<f32, 3>, f32)> {
+	let Some(material) = input.material else {
+		return missing();
+	};
+
+	let alpha = material.alpha(input.uv);
+	(alpha > 0.0).then(|| (material.diffuse(input.uv), alpha))
+}
+
+/// World-space normal mapped from `[-1, 1]` to `[0, 1]`.
+pub fn normals(input: &Input) -> Option<(Array<f32, 3>, f32)> {
+	let Some(normal) = input.normal else {
+		return missing();
+	};
+
+	let color = array![normal[0], normal[1], normal[2]];
+	Some((color * 0.5 + 0.5, 1.0))
+}
+
+/// Diffuse lighting quantized into bands, with a hard specular highlight.
+pub fn toon(input: &Input) -> Option<(Array<f32, 3>, f32)> {
+	let (Some(material), Some(normal)) = (input.material, input.normal) else {
+		return missing();
+	};
+
+	let alpha = material.alpha(input.uv);
+	if alpha == 0.0 {
+		return None;
+	}
+
+	let camera_dir = (input.camera - input.position).normalize();
+	let diffuse_reflection = material.diffuse(input.uv);
+	let specular_reflection = material.specular(input.uv);
+	let specular_exponent = material.specular_exponent(input.uv);
+
+	let color = (input.lights.iter()).fold(material.emissive(input.uv), |sum, light| {
+		let light_dir = (light.position - input.position).normalize();
+		let diffuse = light_dir.dot(normal).clamp(0.0, 1.0);
+		let diffuse = (diffuse * TOON_BANDS).ceil() / TOON_BANDS;
+		let halfway_vector = (light_dir + camera_dir).normalize();
+		let specular = normal.dot(halfway_vector).powi(specular_exponent as i32);
+		let specular = if specular > 0.5 { 1.0 } else { 0.0 };
+
+		sum + diffuse_reflection * diffuse * light.diffuse_color
+			+ specular_reflection * specular * light.specular_color
+	});
+
+	Some((color, alpha))
+}
+
+/// Distance from the camera, from white up close to black far away.
+pub fn depth(input: &Input) -> Option<(Array<f32, 3>, f32)> {
+	let distance = (input.position - input.camera).magnitude();
+	let brightness = DEPTH_HALF / (DEPTH_HALF + distance);
+	Some((array![brightness; 3], 1.0))
+}
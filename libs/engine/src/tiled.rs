@@ -0,0 +1,2141 @@
+use std::{
+	simd::prelude::*,
+	str::FromStr,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		mpsc,
+	},
+	thread, time,
+};
+
+use array::{Array, array};
+use matrix::{Matrix, Vector, vector};
+use render::{
+	bounds::{self, Bounds},
+	buffer::{DepthBuffer, FloatBuffer, ImageBuffer, Pixel, Target},
+	clip,
+	edge::{self, Edge},
+	light,
+	pipeline::Pipeline,
+	varying::Varying,
+};
+
+use crate::{object::Cull, scene::Scene, shader};
+
+#[derive(Clone)]
+struct Triangle {
+	pub bounds: Bounds<usize>,
+	pub edges: [Edge; 3],
+	pub e1: Vector<f32, 3>,
+	pub e2: Vector<f32, 3>,
+	pub e3: Vector<f32, 3>,
+	pub ws: Vector<f32, 3>,
+	pub zs: Vector<f32, 3>,
+	pub w: Vector<f32, 3>,
+	pub near: f32,
+	pub back: bool,
+}
+
+struct Rasterize<V> {
+	pub triangle: Triangle,
+	pub varyings: [V; 3],
+	pub transparent: bool,
+}
+
+/// Output of a [`Pipeline`] fragment stage drawn by [`Tiled::draw_pipeline`].
+#[derive(Copy, Clone, Debug)]
+pub struct Shaded {
+	pub color: Array<f32, 3>,
+	/// Coverage blended over what is already drawn; samples keep their depth either way.
+	pub alpha: f32,
+	/// View-space normal for [`Layers::normals`].
+	pub normal: Option<Vector<f32, 3>>,
+}
+
+trait Stage: Sync {
+	type Varying: Varying + Send + Sync;
+
+	const DEFERRABLE: bool;
+
+	fn shade(&self, varying: Self::Varying, back: bool) -> Option<Shaded>;
+}
+
+struct Programmable<'p, P>(&'p P);
+
+impl<P> Stage for Programmable<'_, P>
+where
+	P: Pipeline<Fragment = Option<Shaded>> + Sync,
+	P::Varying: Send + Sync,
+{
+	type Varying = P::Varying;
+
+	const DEFERRABLE: bool = false;
+
+	fn shade(&self, varying: P::Varying, _back: bool) -> Option<Shaded> {
+		self.0.fragment(varying)
+	}
+}
+
+#[derive(Copy, Clone)]
+struct Surface<'a> {
+	position: Vector<f32, 3>,
+	normal: Option<Vector<f32, 3>>,
+	uv: Option<Vector<f32, 2>>,
+	material: Option<&'a obj::Material>,
+	shader: shader::Shader,
+}
+
+impl Varying for Surface<'_> {
+	fn scale(self, rz: f32) -> Self {
+		Self {
+			position: self.position * rz,
+			normal: self.normal.map(|n| n * rz),
+			uv: self.uv.map(|uv| uv * rz),
+			material: self.material,
+			shader: self.shader,
+		}
+	}
+
+	fn barycentric(a: Self, u: f32, b: Self, v: f32, c: Self, w: f32) -> Self {
+		Self {
+			position: a.position * u + b.position * v + c.position * w,
+			normal: (a.normal.zip(b.normal).zip(c.normal)).map(|((a, b), c)| a * u + b * v + c * w),
+			uv: (a.uv.zip(b.uv).zip(c.uv)).map(|((a, b), c)| a * u + b * v + c * w),
+			material: a.material,
+			shader: a.shader,
+		}
+	}
+}
+
+struct Lit<'a> {
+	camera_position: Vector<f32, 3>,
+	view_space: Matrix<f32, 3, 3>,
+	lights: &'a [light::Light],
+}
+
+impl<'a> Stage for Lit<'a> {
+	type Varying = Surface<'a>;
+
+	// Scene shaders only discard and blend fragments of transparent materials.
+	const DEFERRABLE: bool = true;
+
+	fn shade(&self, surface: Surface<'a>, back: bool) -> Option<Shaded> {
+		let normal = surface.normal.map(|normal| {
+			let normal = normal.normalize();
+			if back { -normal } else { normal }
+		});
+
+		let (color, alpha) = (surface.shader)(&shader::Input {
+			position: surface.position,
+			normal,
+			uv: surface.uv,
+			material: surface.material,
+			camera: self.camera_position,
+			lights: self.lights,
+		})?;
+
+		Some(Shaded {
+			color,
+			alpha,
+			normal: normal.map(|normal| (normal * self.view_space).normalize()),
+		})
+	}
+}
+
+struct Line {
+	pub a: Vector<f32, 4>,
+	pub b: Vector<f32, 4>,
+	pub bounds: Bounds<usize>,
+	pub color: Array<f32, 3>,
+}
+
+struct Point {
+	pub position: Vector<f32, 3>,
+	pub bounds: Bounds<usize>,
+	pub color: Array<f32, 3>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Fragment {
+	x: usize,
+	y: usize,
+	coverage: u8,
+	depths: [f32; 8],
+	weights: Vector<f32, 3>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Primitive {
+	Triangle(u32),
+	Line(u32),
+	Point(u32),
+}
+
+struct Arena<V> {
+	width: usize,
+	height: usize,
+	columns: usize,
+	reversed_z: bool,
+	triangles: Vec<Rasterize<V>>,
+	transparent: Vec<(f32, Rasterize<V>)>,
+	lines: Vec<Line>,
+	points: Vec<Point>,
+	bins: Vec<Vec<Primitive>>,
+}
+
+struct Buffers {
+	pub bounds: Bounds<usize>,
+	pub color: Vec<Array<f32, 3>>,
+	pub depth: Vec<f32>,
+	pub normal: Vec<Vector<f32, 3>>,
+	pub occluded: Occlusion,
+	pub elapsed: time::Duration,
+}
+
+/// Time spent in each stage of one [`Tiled::draw`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Timings {
+	pub vertex: time::Duration,
+	pub setup: time::Duration,
+	pub rasterize: time::Duration,
+	pub compose: time::Duration,
+	pub occluded: Occlusion,
+}
+
+/// Work skipped in one [`Tiled::draw`] because it was hidden behind geometry already drawn.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Occlusion {
+	/// Triangles rejected per tile before any of their blocks were visited.
+	pub triangles: usize,
+	/// Pixel blocks rejected before any of their pixels were tested.
+	pub blocks: usize,
+}
+
+/// Optional buffers filled alongside the color frame by [`Tiled::draw_layers`].
+#[derive(Default)]
+pub struct Layers<'a> {
+	pub hdr: Option<&'a mut FloatBuffer>,
+	pub depth: Option<&'a mut DepthBuffer>,
+	pub normals: Option<&'a mut ImageBuffer>,
+}
+
+/// How [`Tiled`] draws triangles.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Mode {
+	#[default]
+	Filled,
+	Wireframe,
+	WireframeOverShaded,
+	Points,
+}
+
+impl Mode {
+	pub const NAMES: [&str; 4] = ["filled", "wireframe", "wireframe-over-shaded", "points"];
+
+	/// The mode after this one, wrapping around.
+	pub fn next(self) -> Self {
+		match self {
+			Mode::Filled => Mode::Wireframe,
+			Mode::Wireframe => Mode::WireframeOverShaded,
+			Mode::WireframeOverShaded => Mode::Points,
+			Mode::Points => Mode::Filled,
+		}
+	}
+
+	fn filled(self) -> bool {
+		matches!(self, Mode::Filled | Mode::WireframeOverShaded)
+	}
+
+	fn wire_color(self) -> Option<Array<f32, 3>> {
+		match self {
+			Mode::Wireframe => Some(array![1.0, 1.0, 1.0]),
+			Mode::WireframeOverShaded => Some(array![0.0, 0.0, 0.0]),
+			_ => None,
+		}
+	}
+}
+
+impl FromStr for Mode {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> anyhow::Result<Self> {
+		match s {
+			"filled" => Ok(Mode::Filled),
+			"wireframe" => Ok(Mode::Wireframe),
+			"wireframe-over-shaded" => Ok(Mode::WireframeOverShaded),
+			"points" => Ok(Mode::Points),
+			other => anyhow::bail!("unknown mode {}", other),
+		}
+	}
+}
+
+/// When [`Tiled`] runs the lighting model.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Shading {
+	/// Shades every fragment that passes the depth test as it is rasterized.
+	#[default]
+	Forward,
+	/// Writes the surface of each sample to a G-buffer and shades every visible surface once per
+	/// pixel after the tile's opaque triangles are drawn. Only applies to scene objects.
+	Deferred,
+}
+
+impl Shading {
+	pub const NAMES: [&str; 2] = ["forward", "deferred"];
+}
+
+impl FromStr for Shading {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> anyhow::Result<Self> {
+		match s {
+			"forward" => Ok(Shading::Forward),
+			"deferred" => Ok(Shading::Deferred),
+			other => anyhow::bail!("unknown shading {}", other),
+		}
+	}
+}
+
+/// Samples per pixel tested for coverage and depth.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Samples {
+	#[default]
+	One,
+	Two,
+	Four,
+	Eight,
+}
+
+impl Samples {
+	pub const NAMES: [&str; 4] = ["1", "2", "4", "8"];
+
+	pub fn count(self) -> usize {
+		self.pattern().len()
+	}
+
+	fn pattern(self) -> &'static [(f32, f32)] {
+		match self {
+			Samples::One => &[(0.0, 0.0)],
+			Samples::Two => &[(0.25, 0.25), (-0.25, -0.25)],
+			Samples::Four => &[
+				(-0.125, -0.375),
+				(0.375, -0.125),
+				(-0.375, 0.125),
+				(0.125, 0.375),
+			],
+			Samples::Eight => &[
+				(0.0625, -0.1875),
+				(-0.0625, 0.1875),
+				(0.3125, 0.0625),
+				(-0.1875, -0.3125),
+				(-0.3125, 0.3125),
+				(-0.4375, -0.0625),
+				(0.1875, 0.4375),
+				(0.4375, -0.4375),
+			],
+		}
+	}
+}
+
+impl FromStr for Samples {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> anyhow::Result<Self> {
+		match s {
+			"1" => Ok(Samples::One),
+			"2" => Ok(Samples::Two),
+			"4" => Ok(Samples::Four),
+			"8" => Ok(Samples::Eight),
+			other => anyhow::bail!("unsupported sample count {}", other),
+		}
+	}
+}
+
+/// Side of the square tiles the frame is split into, in pixels.
+pub const TILE_SIZE: usize = 64;
+
+/// Multithreaded renderer splitting the frame into tiles that worker threads take from a
+/// shared queue.
+pub struct Tiled {
+	pub mode: Mode,
+	pub shading: Shading,
+	/// Whether a row of pixels is rasterized at once rather than one pixel at a time.
+	pub simd: bool,
+	/// Whether the projection of [`Tiled::draw_pipeline`] puts the near plane at `z = w` rather
+	/// than 0. Scenes follow [`Camera::reversed_z`](crate::Camera::reversed_z) instead.
+	pub reversed_z: bool,
+	threads: usize,
+	pattern: &'static [(f32, f32)],
+	planes: Vec<Vector<f32, 4>>,
+	tiles: Vec<Bounds<usize>>,
+}
+
+impl Tiled {
+	/// Renders a `width` by `height` frame on `threads` workers.
+	pub fn new(threads: usize, width: usize, height: usize, samples: Samples) -> Self {
+		let tiles = (0..height.div_ceil(TILE_SIZE))
+			.flat_map(|row| {
+				(0..width.div_ceil(TILE_SIZE)).map(move |column| Bounds {
+					left: column * TILE_SIZE,
+					right: ((column + 1) * TILE_SIZE).min(width),
+					top: row * TILE_SIZE,
+					bottom: ((row + 1) * TILE_SIZE).min(height),
+				})
+			})
+			.collect();
+
+		Self {
+			mode: Mode::default(),
+			shading: Shading::default(),
+			simd: true,
+			reversed_z: false,
+			threads: threads.max(1),
+			pattern: samples.pattern(),
+			planes: clip::planes(Some(clip::GUARD_BAND)),
+			tiles,
+		}
+	}
+
+	/// Draws `scene` into `frame` through the camera view and `projection`.
+	pub fn draw(
+		&self,
+		frame: impl Target<Unit: Pixel>,
+		scene: &Scene,
+		projection: Matrix<f32, 4, 4>,
+	) -> Timings {
+		self.draw_layers(frame, Layers::default(), scene, projection)
+	}
+
+	/// Like [`Tiled::draw`], also writing the frame's HDR color, depth as `z / w` and view-space
+	/// normals.
+	pub fn draw_layers(
+		&self,
+		mut frame: impl Target<Unit: Pixel>,
+		mut layers: Layers,
+		scene: &Scene,
+		projection: Matrix<f32, 4, 4>,
+	) -> Timings {
+		let mut timings = Timings::default();
+		let reversed_z = scene.camera.reversed_z;
+		let mut arena = Arena::new(frame.width(), frame.height(), reversed_z);
+		let projection = scene.camera.view * projection;
+
+		let lit = Lit {
+			camera_position: scene.camera.position,
+			view_space: scene.camera.view.sub_matrix(3, 3).unwrap(),
+			lights: &scene.lights,
+		};
+
+		for object in scene.objects.iter() {
+			let now = time::Instant::now();
+			let clip_space = object.world_space * projection;
+
+			let (world, clip): (Vec<_>, Vec<_>) = (object.mesh.positions.iter())
+				.map(|v| ((v.v4() * object.world_space).v3(), v.v4() * clip_space))
+				.unzip();
+
+			let normals: Vec<_> = (object.mesh.normals.iter())
+				.map(|v| *v * object.normal_space)
+				.collect();
+
+			timings.vertex += now.elapsed();
+			let now = time::Instant::now();
+
+			if self.mode == Mode::Points {
+				self.points(&mut arena, clip.iter().copied());
+				timings.setup += now.elapsed();
+				continue;
+			}
+
+			for ([v1, v2, v3], material) in object.mesh.triangles() {
+				let cull = object.culling(material);
+				let material = material
+					.and_then(|name| object.mesh.materials.get(name))
+					.map(|material| &**material);
+
+				// Normals or UVs missing on any vertex are missing on the whole triangle.
+				let varyings = [v1, v2, v3].map(|v| Surface {
+					position: world[v.position],
+					normal: v.normal.map(|n| normals[n]),
+					uv: v.uv.map(|uv| object.mesh.uvs[uv]),
+					material,
+					shader: object.shader,
+				});
+
+				let vs = [clip[v1.position], clip[v2.position], clip[v3.position]];
+				let transparent = material.is_some_and(|m| m.transparent());
+				self.setup(&mut arena, vs, varyings, cull, transparent);
+			}
+
+			timings.setup += now.elapsed();
+		}
+
+		self.draw_arena(
+			arena,
+			&lit,
+			compose(&mut frame, &mut layers, reversed_z),
+			&mut timings,
+		);
+		timings
+	}
+
+	/// Draws the triangle list `vertices` through the vertex and fragment stages of `pipeline`,
+	/// discarding the faces selected by `cull`. Pipelines are always shaded forward.
+	pub fn draw_pipeline<P>(
+		&self,
+		mut frame: impl Target<Unit: Pixel>,
+		mut layers: Layers,
+		pipeline: &P,
+		cull: Cull,
+		vertices: impl IntoIterator<Item = P::Vertex>,
+	) -> Timings
+	where
+		P: Pipeline<Fragment = Option<Shaded>> + Sync,
+		P::Varying: Send + Sync,
+	{
+		let mut timings = Timings::default();
+		let mut arena = Arena::new(frame.width(), frame.height(), self.reversed_z);
+
+		let now = time::Instant::now();
+		let vertices: Vec<_> = (vertices.into_iter()).map(|v| pipeline.vertex(v)).collect();
+		timings.vertex += now.elapsed();
+
+		let now = time::Instant::now();
+
+		if self.mode == Mode::Points {
+			self.points(&mut arena, vertices.iter().map(|(v, _)| *v));
+		} else {
+			for triangle in vertices.chunks_exact(3) {
+				let vs = [triangle[0].0, triangle[1].0, triangle[2].0];
+				let varyings = [triangle[0].1, triangle[1].1, triangle[2].1];
+				self.setup(&mut arena, vs, varyings, cull, false);
+			}
+		}
+
+		timings.setup += now.elapsed();
+
+		let shader = Programmable(pipeline);
+		self.draw_arena(
+			arena,
+			&shader,
+			compose(&mut frame, &mut layers, self.reversed_z),
+			&mut timings,
+		);
+		timings
+	}
+
+	fn points<V>(&self, arena: &mut Arena<V>, clip: impl Iterator<Item = Vector<f32, 4>>) {
+		for v in clip {
+			if let Some(mut point) = Point::new(v, arena.width, arena.height) {
+				if arena.reversed_z {
+					point.position[2] = -point.position[2];
+				}
+
+				arena.point(point);
+			}
+		}
+	}
+
+	fn setup<V: Varying>(
+		&self,
+		arena: &mut Arena<V>,
+		vs: [Vector<f32, 4>; 3],
+		varyings: [V; 3],
+		cull: Cull,
+		transparent: bool,
+	) {
+		let (width, height) = (arena.width, arena.height);
+
+		let mut setup = |vs: [Vector<f32, 4>; 3], weights: Option<Matrix<f32, 3, 3>>| {
+			let Some(mut triangle) = Triangle::new(vs, width, height, cull) else {
+				return;
+			};
+
+			if arena.reversed_z {
+				triangle.reverse_depth();
+			}
+
+			if let Some(color) = self.mode.wire_color() {
+				for (i, j) in [(0, 1), (1, 2), (2, 0)] {
+					// Edges introduced by clipping are not part of the mesh.
+					if let Some(b) = weights
+						&& !(0..3).any(|k| b[(i, k)] == 0.0 && b[(j, k)] == 0.0)
+					{
+						continue;
+					}
+
+					let line = Line::new(vs[i], vs[j], width, height, arena.reversed_z, color);
+					arena.line(line);
+				}
+			}
+
+			if !self.mode.filled() {
+				return;
+			}
+
+			// Varyings of clipped vertices are interpolated from the source triangle.
+			let varyings = weights.map_or(varyings, |weights| {
+				let [a, b, c] = varyings;
+				std::array::from_fn(|i| {
+					V::barycentric(a, weights[(i, 0)], b, weights[(i, 1)], c, weights[(i, 2)])
+				})
+			});
+
+			let r = Rasterize {
+				triangle,
+				varyings,
+				transparent,
+			};
+
+			if r.transparent {
+				let depth = vs[0][3].max(vs[1][3]).max(vs[2][3]);
+				arena.transparent.push((depth, r));
+			} else {
+				arena.triangle(r);
+			}
+		};
+
+		if clip::inside(vs, &self.planes) {
+			setup(vs, None);
+		} else {
+			let polygon = clip::polygon(vs, &self.planes);
+
+			for [(c1, b1), (c2, b2), (c3, b3)] in clip::triangles(&polygon) {
+				setup([c1, c2, c3], Some(Matrix::from_row_vectors([b1, b2, b3])));
+			}
+		}
+	}
+
+	fn draw_arena<S: Stage>(
+		&self,
+		mut arena: Arena<S::Varying>,
+		shader: &S,
+		mut compose: impl FnMut(usize, usize, &Buffers, usize),
+		timings: &mut Timings,
+	) {
+		// Transparent triangles are blended after everything opaque, back to front.
+		let now = time::Instant::now();
+		let mut transparent = std::mem::take(&mut arena.transparent);
+		transparent.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+		for (_, r) in transparent {
+			arena.triangle(r);
+		}
+
+		timings.setup += now.elapsed();
+
+		let next = AtomicUsize::new(0);
+		let (send_buffer, receive_buffer) = mpsc::channel::<Buffers>();
+
+		thread::scope(|scope| {
+			let workers: Vec<_> = (0..self.threads)
+				.map(|_| {
+					let send_buffer = send_buffer.clone();
+					let (next, arena) = (&next, &arena);
+
+					scope.spawn(move || {
+						let mut elapsed = time::Duration::ZERO;
+
+						loop {
+							let i = next.fetch_add(1, Ordering::Relaxed);
+
+							let Some(&bounds) = self.tiles.get(i) else {
+								return elapsed;
+							};
+
+							let buffers = draw_tile(
+								bounds,
+								arena,
+								i,
+								shader,
+								self.pattern,
+								self.shading,
+								self.simd,
+							);
+							elapsed += buffers.elapsed;
+							send_buffer.send(buffers).unwrap();
+						}
+					})
+				})
+				.collect();
+
+			drop(send_buffer);
+
+			for buffers in receive_buffer {
+				let now = time::Instant::now();
+				let bounds = buffers.bounds;
+				let width = bounds.right - bounds.left;
+
+				for i in 0..buffers.color.len() {
+					let x = bounds.left + i % width;
+					let y = bounds.top + i / width;
+					compose(x, y, &buffers, i);
+				}
+
+				timings.occluded.triangles += buffers.occluded.triangles;
+				timings.occluded.blocks += buffers.occluded.blocks;
+				timings.compose += now.elapsed();
+			}
+
+			for worker in workers {
+				timings.rasterize = timings.rasterize.max(worker.join().unwrap());
+			}
+		});
+	}
+}
+
+fn compose<'f>(
+	frame: &'f mut impl Target<Unit: Pixel>,
+	layers: &'f mut Layers,
+	reversed_z: bool,
+) -> impl FnMut(usize, usize, &Buffers, usize) + 'f {
+	move |x, y, buffers, i| {
+		frame.put(x, y, Pixel::from_color(buffers.color[i]));
+
+		if let Some(hdr) = &mut layers.hdr {
+			hdr.put(x, y, buffers.color[i]);
+		}
+
+		if let Some(depth) = &mut layers.depth {
+			// Reversed depth is kept negated and exported as the `z / w` of a forward projection,
+			// which is one minus the reversed one.
+			let z = buffers.depth[i];
+			depth.put(x, y, if reversed_z { 1.0 + z } else { z });
+		}
+
+		if let Some(normals) = &mut layers.normals {
+			normals.put(x, y, encode_normal(buffers.normal[i]));
+		}
+	}
+}
+
+impl<V> Arena<V> {
+	fn new(width: usize, height: usize, reversed_z: bool) -> Self {
+		let columns = width.div_ceil(TILE_SIZE);
+
+		Self {
+			width,
+			height,
+			columns,
+			reversed_z,
+			triangles: vec![],
+			transparent: vec![],
+			lines: vec![],
+			points: vec![],
+			bins: (0..columns * height.div_ceil(TILE_SIZE))
+				.map(|_| vec![])
+				.collect(),
+		}
+	}
+
+	fn triangle(&mut self, r: Rasterize<V>) {
+		let index = Primitive::Triangle(self.triangles.len() as u32);
+		self.bin(r.triangle.bounds, index);
+		self.triangles.push(r);
+	}
+
+	fn line(&mut self, line: Line) {
+		let index = Primitive::Line(self.lines.len() as u32);
+		self.bin(line.bounds, index);
+		self.lines.push(line);
+	}
+
+	fn point(&mut self, point: Point) {
+		let index = Primitive::Point(self.points.len() as u32);
+		self.bin(point.bounds, index);
+		self.points.push(point);
+	}
+
+	fn bin(&mut self, bounds: Bounds<usize>, index: Primitive) {
+		if bounds.left >= bounds.right || bounds.top >= bounds.bottom {
+			return;
+		}
+
+		for row in bounds.top / TILE_SIZE..=(bounds.bottom - 1) / TILE_SIZE {
+			for column in bounds.left / TILE_SIZE..=(bounds.right - 1) / TILE_SIZE {
+				self.bins[row * self.columns + column].push(index);
+			}
+		}
+	}
+}
+
+impl Triangle {
+	fn new(vs: [Vector<f32, 4>; 3], width: usize, height: usize, cull: Cull) -> Option<Self> {
+		let bounds = bounds::bounds(vs).map(bounds::scale(width, height))?;
+		let [s1, s2, s3] = vs.map(|v| render::screen_space(v, width as f32, height as f32));
+
+		let (m, back) = match render::adjugate(s1, s2, s3) {
+			Some(_) if cull == Cull::Front => None,
+			Some(m) => Some((m, false)),
+			// Back faces are set up in reverse order and given their own weights back.
+			None if cull == Cull::Back => None,
+			None => render::adjugate(s1, s3, s2).map(|m| {
+				let [e1, e3, e2] = m.row_vectors();
+				(Matrix::from_row_vectors([e1, e2, e3]), true)
+			}),
+		}?;
+
+		let edges = edge::edges([s1, s2, s3])?;
+		let [e1, e2, e3] = m.row_vectors();
+
+		Some(Self {
+			bounds,
+			edges,
+			e1,
+			e2,
+			e3,
+			ws: e1 + e2 + e3,
+			zs: vector![vs[0][2], vs[1][2], vs[2][2]],
+			w: vector![vs[0][3], vs[1][3], vs[2][3]],
+			near: (vs.iter())
+				.map(|v| v[2] / v[3])
+				.fold(f32::INFINITY, f32::min),
+			back,
+		})
+	}
+
+	fn interpolate(&self, x: f32, y: f32) -> Vector<f32, 3> {
+		let sample = vector![x, y, 1.0];
+		let e = vector![
+			self.e1.dot(sample),
+			self.e2.dot(sample),
+			self.e3.dot(sample)
+		];
+		e * (1.0 / self.ws.dot(sample))
+	}
+
+	fn reverse_depth(&mut self) {
+		self.zs = -self.zs;
+		self.near = (0..3)
+			.map(|i| self.zs[i] / self.w[i])
+			.fold(f32::INFINITY, f32::min);
+	}
+}
+
+impl Line {
+	fn new(
+		a: Vector<f32, 4>,
+		b: Vector<f32, 4>,
+		width: usize,
+		height: usize,
+		reversed_z: bool,
+		color: Array<f32, 3>,
+	) -> Self {
+		// Depth at infinity, which the bias moves away from.
+		let far = if reversed_z { 0.0 } else { 1.0 };
+
+		let [a, b] = [a, b].map(|v| {
+			let screen = render::screen_space(v, width as f32, height as f32) / v[3];
+			let z = if reversed_z { -screen[2] } else { screen[2] };
+			let z = z - LINE_DEPTH_BIAS * (far - z);
+			vector![screen[0], screen[1], z, 1.0 / v[3]]
+		});
+
+		let bounds = Bounds {
+			left: a[0].min(b[0]).max(0.0) as usize,
+			right: (a[0].max(b[0]) + 1.0).clamp(0.0, width as f32) as usize,
+			top: a[1].min(b[1]).max(0.0) as usize,
+			bottom: (a[1].max(b[1]) + 1.0).clamp(0.0, height as f32) as usize,
+		};
+
+		Self {
+			a,
+			b,
+			bounds,
+			color,
+		}
+	}
+}
+
+impl Point {
+	fn new(v: Vector<f32, 4>, width: usize, height: usize) -> Option<Self> {
+		if v[2] < 0.0 || v[2] > v[3] || v[0].abs() > v[3] || v[1].abs() > v[3] {
+			return None;
+		}
+
+		let screen = render::screen_space(v, width as f32, height as f32) / v[3];
+		let (x, y) = (screen[0] as usize, screen[1] as usize);
+
+		Some(Self {
+			position: vector![screen[0], screen[1], v[2] / v[3]],
+			bounds: Bounds {
+				left: x.saturating_sub(1),
+				right: (x + 2).min(width),
+				top: y.saturating_sub(1),
+				bottom: (y + 2).min(height),
+			},
+			color: array![1.0, 1.0, 1.0],
+		})
+	}
+}
+
+fn draw_tile<S: Stage>(
+	bounds: Bounds<usize>,
+	arena: &Arena<S::Varying>,
+	tile: usize,
+	shader: &S,
+	pattern: &[(f32, f32)],
+	shading: Shading,
+	simd: bool,
+) -> Buffers {
+	let now = time::Instant::now();
+	let shading = if S::DEFERRABLE {
+		shading
+	} else {
+		Shading::Forward
+	};
+
+	let mut buffers = Tile::new(bounds, pattern.len(), shading, simd);
+
+	for primitive in arena.bins[tile].iter() {
+		match *primitive {
+			Primitive::Triangle(i) => {
+				let r = &arena.triangles[i as usize];
+
+				// Transparent triangles come last and blend over the shaded opaque ones.
+				if r.transparent && buffers.deferred {
+					buffers.light(shader);
+				}
+
+				rasterize(r, shader, pattern, &mut buffers)
+			}
+			Primitive::Line(i) => line(&arena.lines[i as usize], &mut buffers),
+			Primitive::Point(i) => point(&arena.points[i as usize], &mut buffers),
+		}
+	}
+
+	if buffers.deferred {
+		buffers.light(shader);
+	}
+
+	buffers.resolve(now)
+}
+
+struct Deferred<V> {
+	varying: V,
+	back: bool,
+	order: u32,
+}
+
+struct Tile<V> {
+	bounds: Bounds<usize>,
+	samples: usize,
+	color: Vec<Array<f32, 3>>,
+	depth: Vec<f32>,
+	normal: Vec<Vector<f32, 3>>,
+	columns: usize,
+	blocks: Vec<f32>,
+	dirty: Vec<usize>,
+	occluded: Occlusion,
+	deferred: bool,
+	simd: bool,
+	surfaces: Vec<Option<Deferred<V>>>,
+	gbuffer: Vec<u32>,
+	drawn: u32,
+}
+
+impl<V: Varying> Tile<V> {
+	const EMPTY: u32 = u32::MAX;
+
+	fn new(bounds: Bounds<usize>, samples: usize, shading: Shading, simd: bool) -> Self {
+		let width = bounds.right - bounds.left;
+		let height = bounds.bottom - bounds.top;
+		let columns = width.div_ceil(BLOCK_SIZE);
+		let deferred = shading == Shading::Deferred;
+		let slots = if deferred {
+			width * height * samples
+		} else {
+			0
+		};
+
+		Self {
+			bounds,
+			samples,
+			color: vec![array![0.0, 0.0, 0.0]; width * height * samples],
+			depth: vec![f32::INFINITY; width * height * samples],
+			normal: vec![Vector::zero(); width * height],
+			columns,
+			blocks: vec![f32::INFINITY; columns * height.div_ceil(BLOCK_SIZE)],
+			dirty: vec![],
+			occluded: Occlusion::default(),
+			deferred,
+			simd,
+			surfaces: (0..slots).map(|_| None).collect(),
+			gbuffer: vec![Self::EMPTY; slots],
+			drawn: 0,
+		}
+	}
+
+	fn index(&self, x: usize, y: usize) -> usize {
+		(y - self.bounds.top) * (self.bounds.right - self.bounds.left) + (x - self.bounds.left)
+	}
+
+	fn block(&self, x: usize, y: usize) -> usize {
+		let row = (y - self.bounds.top) / BLOCK_SIZE;
+		row * self.columns + (x - self.bounds.left) / BLOCK_SIZE
+	}
+
+	fn occludes(&self, bounds: Bounds<usize>, depth: f32) -> bool {
+		let (left, right) = (
+			self.block(bounds.left, bounds.top),
+			self.block(bounds.right - 1, bounds.top),
+		);
+		let rows = (bounds.bottom - 1) / BLOCK_SIZE - bounds.top / BLOCK_SIZE;
+
+		(0..=rows).all(|row| {
+			let row = row * self.columns;
+			self.blocks[left + row..=right + row]
+				.iter()
+				.all(|&max| depth >= max)
+		})
+	}
+
+	fn touch(&mut self, x: usize, y: usize) {
+		let block = self.block(x, y);
+
+		if self.dirty.last() != Some(&block) && !self.dirty.contains(&block) {
+			self.dirty.push(block);
+		}
+	}
+
+	fn refresh(&mut self) {
+		let width = self.bounds.right - self.bounds.left;
+		let height = self.bounds.bottom - self.bounds.top;
+
+		for block in self.dirty.drain(..) {
+			let (left, top) = (
+				(block % self.columns) * BLOCK_SIZE,
+				(block / self.columns) * BLOCK_SIZE,
+			);
+			let mut max = f32::NEG_INFINITY;
+
+			for y in top..(top + BLOCK_SIZE).min(height) {
+				for x in left..(left + BLOCK_SIZE).min(width) {
+					let i = (y * width + x) * self.samples;
+					max = (self.depth[i..i + self.samples].iter()).fold(max, |max, &d| max.max(d));
+				}
+			}
+
+			self.blocks[block] = max;
+		}
+	}
+
+	fn write(&mut self, j: usize, z: f32, color: Array<f32, 3>) {
+		self.depth[j] = z;
+		self.color[j] = color;
+
+		if self.deferred {
+			self.gbuffer[j] = Self::EMPTY;
+		}
+	}
+
+	fn light(&mut self, shader: &impl Stage<Varying = V>) {
+		let samples = self.samples;
+
+		for pixel in 0..self.normal.len() {
+			let ids = &self.gbuffer[pixel * samples..(pixel + 1) * samples];
+			let mut shaded = [None; 8];
+
+			for s in 0..samples {
+				if ids[s] == Self::EMPTY {
+					continue;
+				}
+
+				shaded[s] = match (0..s).find(|&t| ids[t] == ids[s]) {
+					Some(t) => shaded[t],
+					None => {
+						let surface = self.surfaces[ids[s] as usize].as_ref().unwrap();
+						shader.shade(surface.varying, surface.back)
+					}
+				};
+
+				if let Some(Shaded { color, alpha, .. }) = shaded[s] {
+					let current = self.color[pixel * samples + s];
+					self.color[pixel * samples + s] = color * alpha + current * (1.0 - alpha);
+				}
+			}
+
+			// The normal is the latest drawn surface's, as when shading forward.
+			let latest = (0..samples)
+				.filter(|&s| ids[s] != Self::EMPTY)
+				.max_by_key(|&s| self.surfaces[ids[s] as usize].as_ref().unwrap().order);
+
+			if let Some(normal) = latest
+				.and_then(|s| shaded[s])
+				.and_then(|shaded| shaded.normal)
+			{
+				self.normal[pixel] = normal;
+			}
+		}
+
+		self.deferred = false;
+	}
+
+	fn resolve(self, now: time::Instant) -> Buffers {
+		let samples = self.samples;
+
+		let color = (self.color.chunks(samples))
+			.map(|s| s.iter().fold(array![0.0; 3], |sum, &c| sum + c) / samples as f32)
+			.collect();
+
+		let depth = (self.depth.chunks(samples))
+			.map(|s| s.iter().copied().fold(f32::INFINITY, f32::min))
+			.collect();
+
+		Buffers {
+			bounds: self.bounds,
+			color,
+			depth,
+			normal: self.normal,
+			occluded: self.occluded,
+			elapsed: now.elapsed(),
+		}
+	}
+}
+
+fn encode_normal(normal: Vector<f32, 3>) -> [u8; 3] {
+	if normal == Vector::zero() {
+		return [0, 0, 0];
+	}
+
+	[
+		((normal[0] + 1.0) * 127.5) as u8,
+		((normal[1] + 1.0) * 127.5) as u8,
+		((normal[2] + 1.0) * 127.5) as u8,
+	]
+}
+
+fn rasterize<S: Stage>(
+	r: &Rasterize<S::Varying>,
+	shader: &S,
+	pattern: &[(f32, f32)],
+	tile: &mut Tile<S::Varying>,
+) {
+	let samples = pattern.len();
+	let bounds = tile.bounds.clamp(r.triangle.bounds);
+	let near = r.triangle.near;
+
+	if bounds.left >= bounds.right || bounds.top >= bounds.bottom {
+		return;
+	} else if tile.occludes(bounds, near) {
+		tile.occluded.triangles += 1;
+		return;
+	}
+
+	let mut occluded = 0;
+
+	let blocks: Vec<_> = blocks(bounds, &r.triangle, pattern, |x, y| {
+		let hidden = near >= tile.blocks[tile.block(x, y)];
+		occluded += hidden as usize;
+		hidden
+	})
+	.collect();
+
+	tile.occluded.blocks += occluded;
+	let offsets = offsets(&r.triangle, pattern);
+
+	for block in blocks {
+		for y in block.bounds.top..block.bounds.bottom {
+			if tile.simd {
+				let mut row = row(&r.triangle, pattern, &offsets, &block, y);
+				row.depth_test(tile);
+
+				for fragment in row.fragments() {
+					draw_fragment(r, shader, fragment, tile);
+				}
+
+				continue;
+			}
+
+			for x in block.bounds.left..block.bounds.right {
+				let Some(mut fragment) = pixel(&r.triangle, pattern, &offsets, &block, x, y) else {
+					continue;
+				};
+
+				let i = tile.index(x, y);
+				fragment.coverage = (0..samples)
+					.filter(|&s| fragment.coverage & (1 << s) != 0)
+					.filter(|&s| fragment.depths[s] < tile.depth[i * samples + s])
+					.fold(0u8, |coverage, s| coverage | (1 << s));
+
+				if fragment.coverage != 0 {
+					draw_fragment(r, shader, fragment, tile);
+				}
+			}
+		}
+	}
+
+	tile.refresh();
+}
+
+fn draw_fragment<S: Stage>(
+	r: &Rasterize<S::Varying>,
+	shader: &S,
+	fragment: Fragment,
+	tile: &mut Tile<S::Varying>,
+) {
+	let samples = tile.samples;
+	let i = tile.index(fragment.x, fragment.y);
+	let coverage = fragment.coverage;
+	let covered = || (0..samples).filter(move |&s| coverage & (1 << s) != 0);
+
+	let [a, b, c] = r.varyings;
+	let weights = fragment.weights;
+	let varying = Varying::barycentric(a, weights[0], b, weights[1], c, weights[2]);
+
+	if tile.deferred && !r.transparent {
+		// Each pixel has a slot per sample, one of which no uncovered sample still refers to.
+		let ids = &tile.gbuffer[i * samples..(i + 1) * samples];
+		let slot = (0..samples)
+			.map(|k| (i * samples + k) as u32)
+			.find(|&id| (0..samples).all(|s| coverage & (1 << s) != 0 || ids[s] != id))
+			.unwrap();
+
+		tile.surfaces[slot as usize] = Some(Deferred {
+			varying,
+			back: r.triangle.back,
+			order: tile.drawn,
+		});
+		tile.drawn += 1;
+
+		for s in covered() {
+			tile.depth[i * samples + s] = fragment.depths[s];
+			tile.gbuffer[i * samples + s] = slot;
+		}
+
+		tile.touch(fragment.x, fragment.y);
+	} else if let Some(shaded) = shader.shade(varying, r.triangle.back) {
+		for s in covered() {
+			let current = tile.color[i * samples + s];
+			tile.color[i * samples + s] =
+				shaded.color * shaded.alpha + current * (1.0 - shaded.alpha);
+
+			if !r.transparent {
+				tile.depth[i * samples + s] = fragment.depths[s];
+			}
+		}
+
+		if !r.transparent {
+			if let Some(normal) = shaded.normal {
+				tile.normal[i] = normal;
+			}
+
+			tile.touch(fragment.x, fragment.y);
+		}
+	}
+}
+
+const LINE_DEPTH_BIAS: f32 = 1e-3;
+
+fn line<V: Varying>(line: &Line, tile: &mut Tile<V>) {
+	let bounds = tile.bounds.clamp(line.bounds);
+	let d = line.b - line.a;
+	let steps = d[0].abs().max(d[1].abs()).ceil().max(1.0) as usize;
+
+	// Steps that may land in the tile, with one to spare on each side for rounding.
+	let (mut first, mut last) = (0.0f32, steps as f32);
+
+	for (k, min, max) in [
+		(0, bounds.left, bounds.right),
+		(1, bounds.top, bounds.bottom),
+	] {
+		if d[k] == 0.0 {
+			continue;
+		}
+
+		let t0 = (min as f32 - line.a[k]) / d[k] * steps as f32;
+		let t1 = (max as f32 - line.a[k]) / d[k] * steps as f32;
+		first = first.max(t0.min(t1).floor() - 1.0);
+		last = last.min(t0.max(t1).ceil() + 1.0);
+	}
+
+	if first > last {
+		return;
+	}
+
+	for i in first as usize..=last as usize {
+		let v = line.a + d * (i as f32 / steps as f32);
+		let (x, y) = (v[0].floor(), v[1].floor());
+
+		if x < bounds.left as f32 || y < bounds.top as f32 {
+			continue;
+		}
+
+		let (x, y) = (x as usize, y as usize);
+
+		if x >= bounds.right || y >= bounds.bottom {
+			continue;
+		}
+
+		plot(tile, x, y, v[2], line.color);
+	}
+}
+
+fn point<V: Varying>(point: &Point, tile: &mut Tile<V>) {
+	let bounds = tile.bounds.clamp(point.bounds);
+
+	for y in bounds.top..bounds.bottom {
+		for x in bounds.left..bounds.right {
+			plot(tile, x, y, point.position[2], point.color);
+		}
+	}
+}
+
+fn plot<V: Varying>(tile: &mut Tile<V>, x: usize, y: usize, z: f32, color: Array<f32, 3>) {
+	let i = tile.index(x, y);
+
+	for j in i * tile.samples..(i + 1) * tile.samples {
+		if z < tile.depth[j] {
+			tile.write(j, z, color);
+		}
+	}
+}
+
+const BLOCK_SIZE: usize = 8;
+
+type Lanes<T> = Simd<T, BLOCK_SIZE>;
+
+struct Block {
+	bounds: Bounds<usize>,
+	coverage: edge::Coverage,
+}
+
+fn blocks(
+	bounds: Bounds<usize>,
+	t: &Triangle,
+	pattern: &[(f32, f32)],
+	mut occluded: impl FnMut(usize, usize) -> bool,
+) -> impl Iterator<Item = Block> {
+	// Furthest any sample lies from its pixel center, in sub-pixels.
+	let reach = (pattern.iter())
+		.map(|&(x, y)| (x.abs().max(y.abs()) * edge::SUBPIXELS as f32).ceil() as i64)
+		.max()
+		.unwrap_or(0);
+
+	std::iter::from_coroutine(
+		#[coroutine]
+		move || {
+			// Blocks are aligned to multiples of their size so that they line up across tiles.
+			let top = bounds.top - bounds.top % BLOCK_SIZE;
+			let left = bounds.left - bounds.left % BLOCK_SIZE;
+
+			for top in (top..bounds.bottom).step_by(BLOCK_SIZE) {
+				let (top, bottom) = (top.max(bounds.top), (top + BLOCK_SIZE).min(bounds.bottom));
+
+				for left in (left..bounds.right).step_by(BLOCK_SIZE) {
+					let (left, right) =
+						(left.max(bounds.left), (left + BLOCK_SIZE).min(bounds.right));
+
+					let coverage = edge::classify(
+						&t.edges,
+						edge::center(left) - reach,
+						edge::center(top) - reach,
+						edge::center(right - 1) + reach,
+						edge::center(bottom - 1) + reach,
+					);
+
+					if coverage == edge::Coverage::Outside || occluded(left, top) {
+						continue;
+					}
+
+					yield Block {
+						bounds: Bounds {
+							left,
+							right,
+							top,
+							bottom,
+						},
+						coverage,
+					};
+				}
+			}
+		},
+	)
+}
+
+fn offsets(t: &Triangle, pattern: &[(f32, f32)]) -> Vec<[i64; 3]> {
+	(pattern.iter())
+		.map(|&(x, y)| {
+			let x = (x * edge::SUBPIXELS as f32) as i64;
+			let y = (y * edge::SUBPIXELS as f32) as i64;
+			t.edges.map(|f| f.a * x + f.b * y)
+		})
+		.collect()
+}
+
+fn pixel(
+	t: &Triangle,
+	pattern: &[(f32, f32)],
+	offsets: &[[i64; 3]],
+	block: &Block,
+	x: usize,
+	y: usize,
+) -> Option<Fragment> {
+	let [f1, f2, f3] = t.edges;
+	let (cx, cy) = (edge::center(x), edge::center(y));
+	let (e1, e2, e3) = (f1.at(cx, cy), f2.at(cx, cy), f3.at(cx, cy));
+	let mut coverage = 0;
+	let mut depths = [0.0; 8];
+	let mut shading = None;
+
+	for (s, (&(dx, dy), o)) in pattern.iter().zip(offsets).enumerate() {
+		// Samples of fully covered blocks skip the edge tests.
+		if block.coverage == edge::Coverage::Inside
+			|| f1.inside(e1 + o[0]) && f2.inside(e2 + o[1]) && f3.inside(e3 + o[2])
+		{
+			let weights = t.interpolate(0.5 + x as f32 + dx, 0.5 + y as f32 + dy);
+			depths[s] = weights.dot(t.zs) / weights.dot(t.w);
+			coverage |= 1 << s;
+			shading.get_or_insert(weights);
+		}
+	}
+
+	// Shade at the pixel center when it is covered, else at the first sample.
+	let weights = if block.coverage == edge::Coverage::Inside
+		|| f1.inside(e1) && f2.inside(e2) && f3.inside(e3)
+	{
+		shading.map(|_| t.interpolate(0.5 + x as f32, 0.5 + y as f32))
+	} else {
+		shading
+	}?;
+
+	Some(Fragment {
+		x,
+		y,
+		coverage,
+		depths,
+		weights,
+	})
+}
+
+struct Row {
+	left: usize,
+	y: usize,
+	coverage: Lanes<u8>,
+	depths: [Lanes<f32>; 8],
+	weights: [Lanes<f32>; 3],
+}
+
+impl Row {
+	fn depth_test<V: Varying>(&mut self, tile: &Tile<V>) {
+		let i = tile.index(self.left, self.y);
+		let pixels = Lanes::from_array(std::array::from_fn(|lane| (i + lane) * tile.samples));
+
+		for s in 0..tile.samples {
+			let bit = Lanes::splat(1 << s);
+			let covered = (self.coverage & bit).simd_ne(Lanes::splat(0));
+			let depth = Lanes::gather_select(
+				&tile.depth,
+				covered.cast(),
+				pixels + Lanes::splat(s),
+				Lanes::splat(f32::NEG_INFINITY),
+			);
+
+			let closer = self.depths[s].simd_lt(depth).cast::<i8>();
+			self.coverage &= closer.select(Lanes::splat(u8::MAX), !bit);
+		}
+	}
+
+	fn fragments(self) -> impl Iterator<Item = Fragment> {
+		(0..BLOCK_SIZE)
+			.filter(move |&i| self.coverage[i] != 0)
+			.map(move |i| Fragment {
+				x: self.left + i,
+				y: self.y,
+				coverage: self.coverage[i],
+				depths: self.depths.map(|depth| depth[i]),
+				weights: vector![self.weights[0][i], self.weights[1][i], self.weights[2][i]],
+			})
+	}
+}
+
+fn row(t: &Triangle, pattern: &[(f32, f32)], offsets: &[[i64; 3]], block: &Block, y: usize) -> Row {
+	let Bounds { left, right, .. } = block.bounds;
+	let xs = Lanes::from_array(std::array::from_fn(|i| (left + i) as i64));
+	let active = xs.simd_lt(Lanes::splat(right as i64));
+	let (cx, cy) = (edge::center(left), edge::center(y));
+
+	// Stepped from the leftmost pixel rather than multiplied in every lane.
+	let es = t.edges.map(|f| {
+		let steps = std::array::from_fn(|i| f.a * edge::SUBPIXELS * i as i64);
+		Lanes::splat(f.at(cx, cy)) + Lanes::from_array(steps)
+	});
+
+	let inside = |o: [i64; 3]| {
+		(t.edges.iter().zip(es).zip(o))
+			.map(|((f, e), o)| (e + Lanes::splat(o + f.bias)).simd_gt(Lanes::splat(0)))
+			.fold(active, |inside, edge| inside & edge)
+	};
+
+	let xs: Lanes<f32> = xs.cast();
+	let mut coverage = Lanes::<u8>::splat(0);
+	let mut depths = [Lanes::splat(0.0); 8];
+	let mut first = [Lanes::splat(0.0); 3];
+
+	for (s, (&(dx, dy), &o)) in pattern.iter().zip(offsets).enumerate() {
+		let covered = if block.coverage == edge::Coverage::Inside {
+			active
+		} else {
+			inside(o)
+		};
+
+		if !covered.any() {
+			continue;
+		}
+
+		let x = Lanes::splat(0.5) + xs + Lanes::splat(dx);
+		let weights = interpolate(t, x, Lanes::splat(0.5 + y as f32 + dy));
+		let depth = dot(weights, t.zs) / dot(weights, t.w);
+		let unshaded = coverage.simd_eq(Lanes::splat(0)).cast::<i32>();
+		let covered = covered.cast::<i32>();
+
+		depths[s] = covered.select(depth, depths[s]);
+		first = std::array::from_fn(|i| (covered & unshaded).select(weights[i], first[i]));
+		coverage |= covered
+			.cast::<i8>()
+			.select(Lanes::splat(1 << s), Lanes::splat(0));
+	}
+
+	let center = if block.coverage == edge::Coverage::Inside {
+		active
+	} else {
+		inside([0; 3])
+	};
+
+	let center = center.cast::<i32>();
+	let weights = interpolate(t, Lanes::splat(0.5) + xs, Lanes::splat(0.5 + y as f32));
+
+	Row {
+		left,
+		y,
+		coverage,
+		depths,
+		weights: std::array::from_fn(|i| center.select(weights[i], first[i])),
+	}
+}
+
+fn interpolate(t: &Triangle, x: Lanes<f32>, y: Lanes<f32>) -> [Lanes<f32>; 3] {
+	let plane = |v: Vector<f32, 3>| {
+		Lanes::splat(0.0) + Lanes::splat(v[0]) * x + Lanes::splat(v[1]) * y + Lanes::splat(v[2])
+	};
+
+	let w = Lanes::splat(1.0) / plane(t.ws);
+	[plane(t.e1) * w, plane(t.e2) * w, plane(t.e3) * w]
+}
+
+fn dot(a: [Lanes<f32>; 3], b: Vector<f32, 3>) -> Lanes<f32> {
+	Lanes::splat(0.0)
+		+ a[0] * Lanes::splat(b[0])
+		+ a[1] * Lanes::splat(b[1])
+		+ a[2] * Lanes::splat(b[2])
+}
+
+#[cfg(test)]
+mod tests {
+	use array::{Array, array};
+	use matrix::{Vector, vector};
+
+	use render::{
+		bounds::Bounds,
+		buffer::{FloatBuffer, Texture},
+		edge,
+		pipeline::Pipeline,
+	};
+
+	use super::{
+		Arena, Fragment, Layers, Line, Mode, Primitive, Programmable, Rasterize, Samples, Shaded,
+		Shading, TILE_SIZE, Tile, Tiled, Triangle, blocks, line, offsets, pixel, rasterize, row,
+	};
+	use crate::object::Cull;
+
+	struct Flat;
+
+	impl Pipeline for Flat {
+		type Vertex = (Vector<f32, 4>, Array<f32, 3>);
+		type Varying = Array<f32, 3>;
+		type Fragment = Option<Shaded>;
+
+		fn vertex(&self, vertex: Self::Vertex) -> (Vector<f32, 4>, Array<f32, 3>) {
+			vertex
+		}
+
+		fn fragment(&self, color: Array<f32, 3>) -> Option<Shaded> {
+			(color[0] >= 0.0).then_some(Shaded {
+				color,
+				alpha: 1.0,
+				normal: None,
+			})
+		}
+	}
+
+	const SIZE: usize = 64;
+	const CELLS: usize = 6;
+
+	fn fragments(t: &Triangle, pattern: &[(f32, f32)], simd: bool) -> Vec<Fragment> {
+		let offsets = offsets(t, pattern);
+		let mut fragments = vec![];
+
+		for block in blocks(t.bounds, t, pattern, |_, _| false) {
+			for y in block.bounds.top..block.bounds.bottom {
+				if simd {
+					fragments.extend(row(t, pattern, &offsets, &block, y).fragments());
+				} else {
+					fragments.extend(
+						(block.bounds.left..block.bounds.right)
+							.filter_map(|x| pixel(t, pattern, &offsets, &block, x, y)),
+					);
+				}
+			}
+		}
+
+		fragments
+	}
+
+	fn clip(x: f32, y: f32) -> Vector<f32, 4> {
+		let size = SIZE as f32;
+		vector![2.0 * x / size - 1.0, 1.0 - 2.0 * y / size, 0.5, 1.0]
+	}
+
+	fn tessellate([left, top, right, bottom]: [f32; 4], jitter: bool) -> Vec<[Vector<f32, 4>; 3]> {
+		let mut seed = 1u32;
+		let mut grid = vec![];
+
+		for j in 0..=CELLS {
+			let mut row = vec![];
+
+			for i in 0..=CELLS {
+				let mut offset = [0.0; 2];
+
+				if jitter && (1..CELLS).contains(&i) && (1..CELLS).contains(&j) {
+					for d in offset.iter_mut() {
+						seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+						*d = (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+					}
+				}
+
+				let x = left + (right - left) * (i as f32 + offset[0] * 0.8) / CELLS as f32;
+				let y = top + (bottom - top) * (j as f32 + offset[1] * 0.8) / CELLS as f32;
+				row.push(clip(x, y));
+			}
+
+			grid.push(row);
+		}
+
+		let mut triangles = vec![];
+
+		for j in 0..CELLS {
+			for i in 0..CELLS {
+				let (a, b) = (grid[j][i], grid[j][i + 1]);
+				let (c, d) = (grid[j + 1][i + 1], grid[j + 1][i]);
+
+				if (i + j) % 2 == 0 {
+					triangles.extend([[a, d, c], [a, c, b]]);
+				} else {
+					triangles.extend([[a, d, b], [b, d, c]]);
+				}
+			}
+		}
+
+		triangles
+	}
+
+	#[test]
+	fn watertight_test() {
+		// Vertices on pixel centers put samples exactly on shared edges.
+		let quads = [
+			([3.3, 5.7, 60.1, 58.9], true),
+			([4.5, 6.5, 58.5, 54.5], false),
+		];
+
+		for ([left, top, right, bottom], jitter) in quads {
+			let triangles = tessellate([left, top, right, bottom], jitter);
+
+			for samples in [Samples::One, Samples::Two, Samples::Four, Samples::Eight] {
+				let pattern = samples.pattern();
+				let samples = samples.count();
+				let mut counts = vec![0; SIZE * SIZE * samples];
+
+				for vs in triangles.iter() {
+					let triangle = Triangle::new(*vs, SIZE, SIZE, Cull::Back).unwrap();
+
+					for fragment in fragments(&triangle, pattern, true) {
+						for s in (0..samples).filter(|s| fragment.coverage & (1 << s) != 0) {
+							counts[(fragment.y * SIZE + fragment.x) * samples + s] += 1;
+						}
+					}
+				}
+
+				for (i, count) in counts.into_iter().enumerate() {
+					let (x, y) = ((i / samples) % SIZE, (i / samples) / SIZE);
+					let (dx, dy) = pattern[i % samples];
+					let (x, y) = (x as f32 + 0.5 + dx, y as f32 + 0.5 + dy);
+					let inside = left <= x && x < right && top <= y && y < bottom;
+					assert_eq!(
+						count,
+						inside as usize,
+						"samples={} at {:?}",
+						samples,
+						(x, y)
+					);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn cull_test() {
+		let front = [clip(8.0, 8.0), clip(8.0, 24.0), clip(24.0, 24.0)];
+		let back = [front[0], front[2], front[1]];
+
+		for (cull, want) in [
+			(Cull::Back, [Some(false), None]),
+			(Cull::Front, [None, Some(true)]),
+			(Cull::None, [Some(false), Some(true)]),
+		] {
+			let have = [front, back].map(|vs| Triangle::new(vs, SIZE, SIZE, cull).map(|t| t.back));
+			assert_eq!(have, want, "{:?}", cull);
+		}
+
+		// Back faces keep the weights of their own vertices.
+		let t = Triangle::new(back, SIZE, SIZE, Cull::None).unwrap();
+		let corner = vector![8.5, 23.5, 1.0];
+		let weights = vector![t.e1.dot(corner), t.e2.dot(corner), t.e3.dot(corner)];
+		assert!(weights[2] > weights[0] && weights[2] > weights[1]);
+	}
+
+	#[test]
+	fn occlusion_test() {
+		let quad = |z: f32| {
+			let triangles = tessellate([0.0, 0.0, SIZE as f32, SIZE as f32], false);
+
+			(triangles.into_iter())
+				.map(|vs| Rasterize {
+					triangle: Triangle::new(
+						vs.map(|v| vector![v[0], v[1], z, 1.0]),
+						SIZE,
+						SIZE,
+						Cull::Back,
+					)
+					.unwrap(),
+					varyings: [array![z, z, z]; 3],
+					transparent: false,
+				})
+				.collect::<Vec<_>>()
+		};
+
+		let bounds = Bounds {
+			left: 0,
+			top: 0,
+			right: SIZE,
+			bottom: SIZE,
+		};
+
+		let shader = Programmable(&Flat);
+		let (near, far) = (quad(0.25), quad(0.5));
+
+		for samples in [Samples::One, Samples::Four] {
+			let pattern = samples.pattern();
+			let samples = samples.count();
+
+			// Drawn back to front nothing is hidden.
+			let mut tile = Tile::new(bounds, samples, Shading::Forward, true);
+			far.iter()
+				.chain(near.iter())
+				.for_each(|r| rasterize(r, &shader, pattern, &mut tile));
+			assert_eq!(tile.occluded.triangles, 0);
+			assert_eq!(tile.occluded.blocks, 0);
+			let depth = tile.depth.clone();
+
+			// Drawn front to back every far triangle is rejected with the same result.
+			let mut tile = Tile::new(bounds, samples, Shading::Forward, true);
+			near.iter()
+				.chain(far.iter())
+				.for_each(|r| rasterize(r, &shader, pattern, &mut tile));
+			assert_eq!(tile.occluded.triangles, far.len());
+			assert_eq!(tile.depth, depth);
+		}
+	}
+
+	#[test]
+	fn pipeline_test() {
+		// Colors varying with screen position, interpolated back at each pixel center.
+		let vertices: Vec<_> = tessellate([0.0, 0.0, SIZE as f32, SIZE as f32], true)
+			.into_iter()
+			.flatten()
+			.map(|v| (v, array![(v[0] + 1.0) / 2.0, (1.0 - v[1]) / 2.0, 0.0]))
+			.collect();
+
+		for samples in [Samples::One, Samples::Four] {
+			let tiled = Tiled::new(2, SIZE, SIZE, samples);
+			let mut frame = FloatBuffer::new(SIZE, SIZE);
+			tiled.draw_pipeline(
+				&mut frame,
+				Layers::default(),
+				&Flat,
+				Cull::Back,
+				vertices.clone(),
+			);
+
+			// Pixels split between triangles are shaded at different samples of each.
+			let tolerance = if samples == Samples::One {
+				1e-4
+			} else {
+				0.5 / SIZE as f32
+			};
+
+			let frame = &mut frame;
+
+			for y in 0..SIZE {
+				for x in 0..SIZE {
+					let color = frame.get(x, y);
+					let want = [x, y].map(|v| (v as f32 + 0.5) / SIZE as f32);
+					assert!(
+						(color[0] - want[0]).abs() < tolerance,
+						"{:?} {}",
+						samples,
+						x
+					);
+					assert!(
+						(color[1] - want[1]).abs() < tolerance,
+						"{:?} {}",
+						samples,
+						y
+					);
+				}
+			}
+		}
+
+		// The same faces culled leave the frame empty.
+		let tiled = Tiled::new(2, SIZE, SIZE, Samples::One);
+		let mut frame = FloatBuffer::new(SIZE, SIZE);
+		tiled.draw_pipeline(
+			&mut frame,
+			Layers::default(),
+			&Flat,
+			Cull::Front,
+			vertices.clone(),
+		);
+		let frame = &mut frame;
+		assert!((0..SIZE * SIZE).all(|i| frame.get(i % SIZE, i / SIZE) == array![0.0; 3]));
+
+		// Discarded fragments of a near quad leave the far quad behind them.
+		let quad = |z: f32, color: Array<f32, 3>| {
+			(tessellate([0.0, 0.0, SIZE as f32, SIZE as f32], false).into_iter())
+				.flatten()
+				.map(move |v| (vector![v[0], v[1], z, 1.0], color))
+		};
+
+		let vertices: Vec<_> = quad(0.5, array![0.0, 1.0, 0.0])
+			.chain(quad(0.25, array![-1.0, 0.0, 0.0]))
+			.collect();
+
+		// Deferred shading falls back to forward for pipelines, which may discard.
+		let [mut forward, mut deferred] = [Shading::Forward, Shading::Deferred].map(|shading| {
+			let mut tiled = Tiled::new(2, SIZE, SIZE, Samples::One);
+			tiled.shading = shading;
+			let mut frame = FloatBuffer::new(SIZE, SIZE);
+			tiled.draw_pipeline(
+				&mut frame,
+				Layers::default(),
+				&Flat,
+				Cull::Back,
+				vertices.clone(),
+			);
+			frame
+		});
+
+		let (forward, deferred) = (&mut forward, &mut deferred);
+
+		for y in 0..SIZE {
+			for x in 0..SIZE {
+				let color = forward.get(x, y);
+				assert!(
+					color[0] == 0.0 && (color[1] - 1.0).abs() < 1e-4,
+					"at {:?}",
+					(x, y)
+				);
+				assert_eq!(deferred.get(x, y), color, "at {:?}", (x, y));
+			}
+		}
+	}
+
+	#[test]
+	fn simd_test() {
+		let mut triangles = tessellate([3.3, 5.7, 60.1, 58.9], true);
+
+		// Slanted in depth to cut through the quad.
+		let slanted = [clip(1.3, 2.7), clip(3.1, 62.2), clip(61.9, 30.4)];
+		triangles.push(slanted.map(|mut v| {
+			v[2] = 0.3 + 0.2 * (v[0] + 1.0);
+			v
+		}));
+
+		let bounds = Bounds {
+			left: 0,
+			top: 0,
+			right: SIZE,
+			bottom: SIZE,
+		};
+
+		let shader = Programmable(&Flat);
+
+		for samples in [Samples::One, Samples::Two, Samples::Four, Samples::Eight] {
+			let pattern = samples.pattern();
+			let samples = samples.count();
+			let mut tiles =
+				[false, true].map(|simd| Tile::new(bounds, samples, Shading::Forward, simd));
+
+			for (i, vs) in triangles.iter().enumerate() {
+				let triangle = Triangle::new(*vs, SIZE, SIZE, Cull::None).unwrap();
+				assert_eq!(
+					fragments(&triangle, pattern, false),
+					fragments(&triangle, pattern, true),
+					"samples={}",
+					samples
+				);
+
+				let r = Rasterize {
+					triangle,
+					varyings: [
+						array![i as f32, 1.0, 0.0],
+						array![0.0, 1.0, 1.0],
+						array![1.0; 3],
+					],
+					transparent: false,
+				};
+
+				for tile in tiles.iter_mut() {
+					rasterize(&r, &shader, pattern, tile);
+				}
+			}
+
+			let [scalar, simd] = tiles;
+			assert_eq!(scalar.depth, simd.depth, "samples={}", samples);
+			assert_eq!(scalar.color, simd.color, "samples={}", samples);
+		}
+	}
+
+	#[test]
+	fn line_test() {
+		let bounds = Bounds {
+			left: 0,
+			top: 0,
+			right: SIZE,
+			bottom: SIZE,
+		};
+
+		let (red, blue, white) = (array![1.0, 0.0, 0.0], array![0.0, 0.0, 1.0], array![1.0; 3]);
+		let corners = [8.0, 8.0, 56.0, 56.0];
+
+		for reversed_z in [false, true] {
+			// Reversed depth puts the near plane at 1 and is negated by the triangle setup.
+			let depth = |z: f32| if reversed_z { 1.0 - z } else { z };
+			let quad = |[left, top, right, bottom]: [f32; 4], z: f32, color: Array<f32, 3>| {
+				(tessellate([left, top, right, bottom], false).into_iter())
+					.map(|vs| {
+						let vs = vs.map(|v| vector![v[0], v[1], depth(z), 1.0]);
+						let mut triangle = Triangle::new(vs, SIZE, SIZE, Cull::None).unwrap();
+
+						if reversed_z {
+							triangle.reverse_depth();
+						}
+
+						Rasterize {
+							triangle,
+							varyings: [color; 3],
+							transparent: false,
+						}
+					})
+					.collect::<Vec<_>>()
+			};
+
+			let mut tile = Tile::new(bounds, 1, Shading::Forward, true);
+			let shader = Programmable(&Flat);
+
+			(quad(corners, 0.75, red).iter())
+				.chain(quad([0.0, 0.0, 32.0, SIZE as f32], 0.25, blue).iter())
+				.for_each(|r| rasterize(r, &shader, Samples::One.pattern(), &mut tile));
+
+			// The top edge of the far quad, drawn over it after the near quad.
+			let [a, b] = [clip(8.5, 8.5), clip(55.5, 8.5)].map(|mut v| {
+				v[2] = depth(0.75);
+				v
+			});
+
+			line(&Line::new(a, b, SIZE, SIZE, reversed_z, white), &mut tile);
+
+			let shows = |x, y, want: Array<f32, 3>| {
+				let color = tile.color[tile.index(x, y)];
+				(0..3).all(|i| (color[i] - want[i]).abs() < 1e-5)
+			};
+
+			for x in 9..55 {
+				let want = if x < 32 { blue } else { white };
+				assert!(shows(x, 8, want), "reversed={} at {}", reversed_z, x);
+			}
+
+			assert!(shows(40, 30, red), "reversed={}", reversed_z);
+		}
+	}
+
+	#[test]
+	fn line_tiles_test() {
+		// Lines drawn tile by tile plot the same pixels as across one tile.
+		let segments = [
+			(clip(1.5, 2.5), clip(62.5, 60.5)),
+			(clip(60.2, 3.7), clip(20.1, 63.9)),
+			(clip(-20.0, 30.5), clip(90.0, 33.5)),
+			(clip(17.5, -5.0), clip(17.5, 70.0)),
+		];
+		let bounds = |left, top, size| Bounds {
+			left,
+			top,
+			right: left + size,
+			bottom: top + size,
+		};
+
+		for (a, b) in segments {
+			let line = Line::new(a, b, SIZE, SIZE, false, array![1.0; 3]);
+			let mut whole =
+				Tile::<Array<f32, 3>>::new(bounds(0, 0, SIZE), 1, Shading::Forward, true);
+			super::line(&line, &mut whole);
+
+			for top in (0..SIZE).step_by(16) {
+				for left in (0..SIZE).step_by(16) {
+					let mut tile = Tile::<Array<f32, 3>>::new(
+						bounds(left, top, 16),
+						1,
+						Shading::Forward,
+						true,
+					);
+					super::line(&line, &mut tile);
+
+					for y in top..top + 16 {
+						for x in left..left + 16 {
+							assert_eq!(
+								tile.depth[tile.index(x, y)],
+								whole.depth[whole.index(x, y)],
+								"at {:?}",
+								(x, y)
+							);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn clipped_edges_test() {
+		// Clipped by the near plane where it crosses the middle of the frame.
+		let vertices = [clip(8.0, 8.0), clip(32.0, 56.0), clip(56.0, 8.0)]
+			.map(|v| (v, array![0.0; 3]))
+			.map(|(mut v, color)| {
+				v[2] = if v[1] < 0.0 { -0.5 } else { 0.5 };
+				(v, color)
+			});
+
+		let mut tiled = Tiled::new(2, SIZE, SIZE, Samples::One);
+		tiled.mode = Mode::Wireframe;
+		let mut frame = FloatBuffer::new(SIZE, SIZE);
+		tiled.draw_pipeline(
+			&mut frame,
+			Layers::default(),
+			&Flat,
+			Cull::Back,
+			vertices.to_vec(),
+		);
+		let frame = &mut frame;
+
+		// The edge of the mesh in front of the near plane is drawn.
+		for x in 10..54 {
+			assert_eq!(frame.get(x, 8), array![1.0; 3], "at {}", x);
+		}
+
+		// Neither the near plane edge at y = 32 nor a diagonal splitting the clipped quad is.
+		for y in 28..36 {
+			for x in 25..40 {
+				assert_eq!(frame.get(x, y), array![0.0; 3], "at {:?}", (x, y));
+			}
+		}
+
+		assert_eq!(frame.get(26, 20), array![0.0; 3]);
+		assert_eq!(frame.get(38, 20), array![0.0; 3]);
+	}
+
+	#[test]
+	fn tiles_test() {
+		let (width, height) = (150, 100);
+		let tiled = Tiled::new(3, width, height, Samples::One);
+		let mut counts = vec![0; width * height];
+
+		for tile in tiled.tiles.iter() {
+			assert!(tile.right - tile.left <= TILE_SIZE && tile.bottom - tile.top <= TILE_SIZE);
+
+			for y in tile.top..tile.bottom {
+				for x in tile.left..tile.right {
+					counts[y * width + x] += 1;
+				}
+			}
+		}
+
+		assert!(counts.into_iter().all(|count| count == 1));
+
+		// Primitives are binned into exactly the tiles they overlap, edges excluded.
+		let mut arena = Arena::<Array<f32, 3>>::new(width, height, false);
+		let primitives = [
+			[0, width, 0, height],
+			[TILE_SIZE, 2 * TILE_SIZE, 0, TILE_SIZE],
+			[TILE_SIZE - 1, TILE_SIZE + 1, TILE_SIZE - 1, TILE_SIZE + 1],
+			[140, 150, 90, 100],
+			[10, 10, 0, 20],
+		]
+		.map(|[left, right, top, bottom]| Bounds {
+			left,
+			right,
+			top,
+			bottom,
+		});
+
+		for (i, &bounds) in primitives.iter().enumerate() {
+			arena.bin(bounds, Primitive::Point(i as u32));
+		}
+
+		for (tile, bin) in tiled.tiles.iter().zip(arena.bins.iter()) {
+			let want: Vec<_> = (primitives.iter().enumerate())
+				.map(|(i, bounds)| (i, tile.clamp(*bounds)))
+				.filter(|(_, overlap)| overlap.left < overlap.right && overlap.top < overlap.bottom)
+				.map(|(i, _)| Primitive::Point(i as u32))
+				.collect();
+			assert_eq!(*bin, want, "{:?}", tile);
+		}
+	}
+
+	#[test]
+	fn blocks_test() {
+		let vs = [clip(1.3, 2.7), clip(3.1, 62.2), clip(61.9, 30.4)];
+		let triangle = Triangle::new(vs, SIZE, SIZE, Cull::None).unwrap();
+
+		for samples in [Samples::One, Samples::Four, Samples::Eight] {
+			let pattern = samples.pattern();
+			let mut have = vec![0u8; SIZE * SIZE];
+
+			for fragment in fragments(&triangle, pattern, true) {
+				have[fragment.y * SIZE + fragment.x] = fragment.coverage;
+			}
+
+			for (i, coverage) in have.into_iter().enumerate() {
+				let (x, y) = (i % SIZE, i / SIZE);
+				let want = (pattern.iter().enumerate())
+					.filter(|(_, (dx, dy))| {
+						let x = edge::center(x) + (dx * edge::SUBPIXELS as f32) as i64;
+						let y = edge::center(y) + (dy * edge::SUBPIXELS as f32) as i64;
+						triangle.edges.iter().all(|e| e.inside(e.at(x, y)))
+					})
+					.fold(0u8, |mask, (s, _)| mask | (1 << s));
+
+				assert_eq!(coverage, want, "samples={:?} at {:?}", samples, (x, y));
+			}
+		}
+	}
+}
@@ -0,0 +1,237 @@
+use std::path::{Path, PathBuf};
+
+use engine::{Samples, Scene, Shading, Tiled};
+use image::RgbImage;
+use matrix::Matrix;
+use render::buffer::{ImageBuffer, Texture};
+
+const ROOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../..");
+const GOLDEN: &str = "scenes/golden";
+const OUTPUT: &str = "target/golden";
+
+const WIDTH: usize = 128;
+const HEIGHT: usize = 128;
+const THREADS: usize = 4;
+
+const TOLERANCE: u8 = 2;
+const MAX_MISMATCH: f64 = 0.001;
+const MIN_PSNR: f64 = 40.0;
+
+/// A way of drawing a scene, by the suffix of its failure images.
+type Variant = (&'static str, fn(&mut Scene, &mut Tiled));
+
+/// Ways of drawing a scene that must all match its reference image.
+const VARIANTS: [Variant; 4] = [
+	("", |_, _| {}),
+	("deferred", |_, tiled| tiled.shading = Shading::Deferred),
+	("reversed", |scene, _| scene.camera.reversed_z = true),
+	("reversed-far", |scene, _| {
+		scene.camera.reversed_z = true;
+		scene.camera.far = Some(100.0);
+	}),
+];
+
+struct Comparison {
+	mismatch: f64,
+	psnr: f64,
+	diff: RgbImage,
+}
+
+#[test]
+fn golden_test() {
+	std::env::set_current_dir(ROOT).unwrap();
+	let update = std::env::var_os("D3_UPDATE_GOLDEN").is_some();
+	let mut failures = vec![];
+
+	for path in scenes() {
+		let name = path.file_stem().unwrap().to_str().unwrap();
+		let reference = Path::new(GOLDEN).join(format!("{}.png", name));
+
+		if let Some(mesh) = missing_mesh(&path) {
+			eprintln!("skip {}: missing {}", name, mesh);
+			continue;
+		}
+
+		if update {
+			std::fs::create_dir_all(GOLDEN).unwrap();
+			render(&path, Samples::One, VARIANTS[0].1)
+				.save(&reference)
+				.unwrap();
+			continue;
+		}
+
+		let Ok(expected) = image::open(&reference).map(|image| image.to_rgb8()) else {
+			failures.push(format!("{}: missing {}", name, reference.display()));
+			continue;
+		};
+
+		for (variant, configure) in VARIANTS {
+			let actual = render(&path, Samples::One, configure);
+			let name = [name, variant].join(if variant.is_empty() { "" } else { "-" });
+
+			let comparison = compare(&actual, &expected);
+
+			if comparison.mismatch > MAX_MISMATCH || comparison.psnr < MIN_PSNR {
+				std::fs::create_dir_all(OUTPUT).unwrap();
+				let output = Path::new(OUTPUT);
+				actual
+					.save(output.join(format!("{}-actual.png", name)))
+					.unwrap();
+				(comparison.diff)
+					.save(output.join(format!("{}-diff.png", name)))
+					.unwrap();
+
+				failures.push(format!(
+					"{}: mismatch={:.4}; psnr={:.2}",
+					name, comparison.mismatch, comparison.psnr
+				));
+			}
+		}
+	}
+
+	assert!(
+		failures.is_empty(),
+		"golden images differ (set D3_UPDATE_GOLDEN=1 to update):\n{}",
+		failures.join("\n")
+	);
+}
+
+#[test]
+fn simd_test() {
+	std::env::set_current_dir(ROOT).unwrap();
+
+	for path in scenes().iter().filter(|path| missing_mesh(path).is_none()) {
+		let scalar = render(path, Samples::One, |_, tiled| tiled.simd = false);
+		let simd = render(path, Samples::One, |_, tiled| tiled.simd = true);
+		assert!(
+			scalar == simd,
+			"{}: SIMD and scalar images differ",
+			path.display()
+		);
+	}
+}
+
+#[test]
+fn deferred_test() {
+	std::env::set_current_dir(ROOT).unwrap();
+
+	// An opaque quad behind one transparent triangle, both with edges inside pixels.
+	let files = [
+		(
+			"scene.toml",
+			"[camera]\nposition = [0.0, 0.0, 3.0]\n\n[[objects]]\nmesh = \"target/deferred/quad.obj\"\n",
+		),
+		(
+			"quad.obj",
+			"mtllib quad.mtl\n\
+			v -1.1 -0.9 0.0\nv 0.9 -1.0 0.0\nv 1.0 1.1 0.0\nv -0.9 0.8 0.0\n\
+			v -0.8 -0.6 0.5\nv 0.9 -0.2 0.5\nv -0.1 0.9 0.5\n\
+			vn 0.0 0.0 1.0\n\
+			g quad\nusemtl opaque\nf 1//1 2//1 3//1\nf 1//1 3//1 4//1\n\
+			g glass\nusemtl glass\nf 5//1 6//1 7//1\n",
+		),
+		(
+			"quad.mtl",
+			"newmtl opaque\nKd 0.8 0.4 0.1\nd 1.0\n\nnewmtl glass\nKd 0.1 0.5 0.8\nd 0.5\n",
+		),
+	];
+
+	let directory = Path::new("target/deferred");
+	std::fs::create_dir_all(directory).unwrap();
+
+	for (name, contents) in files {
+		std::fs::write(directory.join(name), contents).unwrap();
+	}
+
+	let path = directory.join("scene.toml");
+	let forward = render(&path, Samples::Four, |_, tiled| {
+		tiled.shading = Shading::Forward
+	});
+	let deferred = render(&path, Samples::Four, |_, tiled| {
+		tiled.shading = Shading::Deferred
+	});
+	assert!(forward == deferred, "forward and deferred images differ");
+}
+
+fn scenes() -> Vec<PathBuf> {
+	let mut paths: Vec<_> = std::fs::read_dir("scenes")
+		.unwrap()
+		.map(|entry| entry.unwrap().path())
+		.filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+		.collect();
+
+	paths.sort();
+	paths
+}
+
+fn missing_mesh(path: &Path) -> Option<String> {
+	let table = std::fs::read_to_string(path)
+		.unwrap()
+		.parse::<toml::Table>()
+		.unwrap();
+
+	(table.get("objects").and_then(|v| v.as_array()))
+		.into_iter()
+		.flatten()
+		.filter_map(|object| object.get("mesh").and_then(|v| v.as_str()))
+		.find(|mesh| !Path::new(mesh).exists())
+		.map(String::from)
+}
+
+fn render(path: &Path, samples: Samples, configure: fn(&mut Scene, &mut Tiled)) -> RgbImage {
+	let mut scene = Scene::load(path.to_str().unwrap()).unwrap();
+
+	if scene.lights.is_empty() {
+		scene.camera_light();
+	}
+
+	let mut tiled = Tiled::new(THREADS, WIDTH, HEIGHT, samples);
+	configure(&mut scene, &mut tiled);
+	let projection: Matrix<f32, 4, 4> = scene.camera.projection(1.0);
+	let mut frame = ImageBuffer::new(WIDTH, HEIGHT);
+	tiled.draw(&mut frame, &scene, projection);
+
+	let frame = &mut frame;
+	RgbImage::from_fn(WIDTH as u32, HEIGHT as u32, |x, y| {
+		image::Rgb(frame.get(x as usize, y as usize))
+	})
+}
+
+fn compare(actual: &RgbImage, expected: &RgbImage) -> Comparison {
+	if actual.dimensions() != expected.dimensions() {
+		return Comparison {
+			mismatch: 1.0,
+			psnr: 0.0,
+			diff: RgbImage::new(actual.width(), actual.height()),
+		};
+	}
+
+	let mut mismatched = 0;
+	let mut squared_error = 0.0;
+
+	let diff = RgbImage::from_fn(actual.width(), actual.height(), |x, y| {
+		let a = actual.get_pixel(x, y);
+		let b = expected.get_pixel(x, y);
+		let d: [u8; 3] = std::array::from_fn(|i| a[i].abs_diff(b[i]));
+
+		if d.iter().any(|&d| d > TOLERANCE) {
+			mismatched += 1;
+		}
+
+		squared_error += d.iter().map(|&d| (d as f64).powi(2)).sum::<f64>();
+		image::Rgb(d.map(|d| d.saturating_mul(8)))
+	});
+
+	let pixels = (actual.width() * actual.height()) as f64;
+	let mse = squared_error / (pixels * 3.0);
+
+	Comparison {
+		mismatch: mismatched as f64 / pixels,
+		psnr: if mse == 0.0 {
+			f64::INFINITY
+		} else {
+			10.0 * (255.0 * 255.0 / mse).log10()
+		},
+		diff,
+	}
+}
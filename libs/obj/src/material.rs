@@ -44,6 +44,11 @@ impl Material {
 		}
 	}
 
+	#[inline]
+	pub fn transparent(&self) -> bool {
+		self.alpha < 1.0 || self.alpha_map.is_some()
+	}
+
 	#[inline]
 	pub fn ambient(&self, uv: Option<Vector<f32, 2>>) -> Array<f32, 3> {
 		if let Some(uv) = uv
@@ -1,3 +1,5 @@
+use array::Array;
+
 pub trait Buffer {
 	type Unit;
 
@@ -13,6 +15,35 @@ pub trait Texture: Buffer {
 	fn get(&self, x: usize, y: usize) -> Self::Unit;
 }
 
+pub trait Pixel {
+	fn from_color(color: Array<f32, 3>) -> Self;
+}
+
+impl Pixel for Array<f32, 3> {
+	fn from_color(color: Array<f32, 3>) -> Self {
+		color
+	}
+}
+
+impl Pixel for [u8; 3] {
+	fn from_color(color: Array<f32, 3>) -> Self {
+		let color = color.clamp(0.0, 1.0);
+
+		[
+			(color[0] * 255.0) as u8,
+			(color[1] * 255.0) as u8,
+			(color[2] * 255.0) as u8,
+		]
+	}
+}
+
+impl Pixel for [u8; 4] {
+	fn from_color(color: Array<f32, 3>) -> Self {
+		let [r, g, b] = <[u8; 3]>::from_color(color);
+		[r, g, b, 255]
+	}
+}
+
 pub struct ImageBuffer {
 	buffer: image::ImageBuffer<image::Rgb<u8>, Vec<u8>>,
 }
@@ -24,8 +55,8 @@ impl ImageBuffer {
 		}
 	}
 
-	pub fn save(&self, path: &str) {
-		self.buffer.save(path).unwrap();
+	pub fn save(&self, path: &str) -> image::ImageResult<()> {
+		self.buffer.save(path)
 	}
 }
 
@@ -47,6 +78,110 @@ impl Target for &mut ImageBuffer {
 	}
 }
 
+impl Texture for &mut ImageBuffer {
+	fn get(&self, x: usize, y: usize) -> Self::Unit {
+		self.buffer.get_pixel(x as u32, y as u32).0
+	}
+}
+
+pub struct RgbaBuffer {
+	buffer: image::RgbaImage,
+}
+
+impl RgbaBuffer {
+	pub fn new(width: usize, height: usize) -> Self {
+		Self {
+			buffer: image::RgbaImage::new(width as u32, height as u32),
+		}
+	}
+
+	pub fn save(&self, path: &str) -> image::ImageResult<()> {
+		self.buffer.save(path)
+	}
+}
+
+impl Buffer for &mut RgbaBuffer {
+	type Unit = [u8; 4];
+
+	fn width(&self) -> usize {
+		self.buffer.width() as usize
+	}
+
+	fn height(&self) -> usize {
+		self.buffer.height() as usize
+	}
+}
+
+impl Target for &mut RgbaBuffer {
+	fn put(&mut self, x: usize, y: usize, pixel: Self::Unit) {
+		self.buffer
+			.put_pixel(x as u32, y as u32, image::Rgba(pixel))
+	}
+}
+
+impl Texture for &mut RgbaBuffer {
+	fn get(&self, x: usize, y: usize) -> Self::Unit {
+		self.buffer.get_pixel(x as u32, y as u32).0
+	}
+}
+
+pub struct FloatBuffer {
+	width: usize,
+	height: usize,
+	buffer: Vec<Array<f32, 3>>,
+}
+
+impl FloatBuffer {
+	pub fn new(width: usize, height: usize) -> Self {
+		Self {
+			width,
+			height,
+			buffer: vec![Array::zero(); width * height],
+		}
+	}
+
+	pub fn save(&self, path: &str) -> image::ImageResult<()> {
+		if path.ends_with(".pfm") {
+			let data: Vec<f32> = (self.buffer.iter())
+				.flat_map(|&color| <[f32; 3]>::from(color))
+				.collect();
+
+			crate::pfm::write(path, self.width, self.height, 3, &data)?;
+			return Ok(());
+		}
+
+		let image = image::Rgb32FImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+			image::Rgb(self.buffer[y as usize * self.width + x as usize].into())
+		});
+
+		image.save(path)
+	}
+}
+
+impl Buffer for &mut FloatBuffer {
+	type Unit = Array<f32, 3>;
+
+	fn width(&self) -> usize {
+		self.width
+	}
+
+	fn height(&self) -> usize {
+		self.height
+	}
+}
+
+impl Texture for &mut FloatBuffer {
+	fn get(&self, x: usize, y: usize) -> Self::Unit {
+		self.buffer[y * self.width + x]
+	}
+}
+
+impl Target for &mut FloatBuffer {
+	fn put(&mut self, x: usize, y: usize, color: Self::Unit) {
+		self.buffer[y * self.width + x] = color;
+	}
+}
+
 pub struct DepthBuffer {
 	width: usize,
 	height: usize,
@@ -61,6 +196,22 @@ impl DepthBuffer {
 			buffer: vec![initial; width * height],
 		}
 	}
+
+	/// Saves the raw depths as a single-channel PFM, or as a 16-bit PNG mapping depth 0 to 1
+	/// linearly onto 0 to 65535 for every frame alike, with empty pixels at 65535.
+	pub fn save(&self, path: &str) -> image::ImageResult<()> {
+		if path.ends_with(".pfm") {
+			crate::pfm::write(path, self.width, self.height, 1, &self.buffer)?;
+			return Ok(());
+		}
+
+		let image = image::ImageBuffer::from_fn(self.width as u32, self.height as u32, |x, y| {
+			let z = self.buffer[y as usize * self.width + x as usize];
+			image::Luma([(z.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16])
+		});
+
+		image.save(path)
+	}
 }
 
 impl Buffer for &mut DepthBuffer {
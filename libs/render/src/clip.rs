@@ -0,0 +1,109 @@
+use matrix::{Matrix, Vector, vector};
+
+/// Multiple of the viewport, in each direction, that triangles may extend past before they are
+/// clipped against the sides.
+pub const GUARD_BAND: f32 = 16.0;
+
+pub type Vertex = (Vector<f32, 4>, Vector<f32, 3>);
+
+pub fn planes(guard_band: Option<f32>) -> Vec<Vector<f32, 4>> {
+	let mut planes = vec![vector![0.0, 0.0, 1.0, 0.0], vector![0.0, 0.0, -1.0, 1.0]];
+
+	if let Some(k) = guard_band {
+		planes.push(vector![1.0, 0.0, 0.0, k]);
+		planes.push(vector![-1.0, 0.0, 0.0, k]);
+		planes.push(vector![0.0, 1.0, 0.0, k]);
+		planes.push(vector![0.0, -1.0, 0.0, k]);
+	}
+
+	planes
+}
+
+#[inline]
+pub fn inside(vs: [Vector<f32, 4>; 3], planes: &[Vector<f32, 4>]) -> bool {
+	(planes.iter()).all(|p| vs.iter().all(|v| p.dot(*v) >= 0.0))
+}
+
+pub fn polygon(vs: [Vector<f32, 4>; 3], planes: &[Vector<f32, 4>]) -> Vec<Vertex> {
+	let identity = Matrix::<f32, 3, 3>::identity().row_vectors();
+	let mut polygon: Vec<Vertex> = vs.into_iter().zip(identity).collect();
+
+	for plane in planes {
+		if polygon.is_empty() {
+			break;
+		}
+
+		let mut output = Vec::with_capacity(polygon.len() + 1);
+
+		for (i, &(v1, b1)) in polygon.iter().enumerate() {
+			let (v2, b2) = polygon[(i + 1) % polygon.len()];
+			let d1 = plane.dot(v1);
+			let d2 = plane.dot(v2);
+
+			if d1 >= 0.0 {
+				output.push((v1, b1));
+			}
+
+			if (d1 >= 0.0) != (d2 >= 0.0) {
+				let t = d1 / (d1 - d2);
+				output.push((v1 + (v2 - v1) * t, b1 + (b2 - b1) * t));
+			}
+		}
+
+		polygon = output;
+	}
+
+	polygon
+}
+
+pub fn triangles(polygon: &[Vertex]) -> impl Iterator<Item = [Vertex; 3]> + '_ {
+	(1..polygon.len().saturating_sub(1)).map(|i| [polygon[0], polygon[i], polygon[i + 1]])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn inside_test() {
+		let vs = [
+			vector![-1.0, -1.0, 1.0, 2.0],
+			vector![1.0, -1.0, 1.0, 2.0],
+			vector![0.0, 1.0, 1.0, 2.0],
+		];
+
+		assert!(inside(vs, &planes(Some(GUARD_BAND))));
+		assert_eq!(polygon(vs, &planes(None)).len(), 3);
+	}
+
+	#[test]
+	fn near_test() {
+		let vs = [
+			vector![-1.0, 0.0, -1.0, 1.0],
+			vector![1.0, 0.0, 1.0, 3.0],
+			vector![0.0, 1.0, 1.0, 3.0],
+		];
+
+		let polygon = polygon(vs, &planes(None));
+		assert_eq!(polygon.len(), 4);
+		assert_eq!(triangles(&polygon).count(), 2);
+
+		for (v, b) in polygon {
+			assert!(v[2] >= 0.0);
+			assert!((b[0] + b[1] + b[2] - 1.0).abs() < 1e-6);
+			let want = vs[0] * b[0] + vs[1] * b[1] + vs[2] * b[2];
+			assert!((want - v).magnitude() < 1e-6);
+		}
+	}
+
+	#[test]
+	fn behind_test() {
+		let vs = [
+			vector![-1.0, 0.0, -1.0, 1.0],
+			vector![1.0, 0.0, -1.0, 1.0],
+			vector![0.0, 1.0, -1.0, 1.0],
+		];
+
+		assert!(polygon(vs, &planes(None)).is_empty());
+	}
+}
@@ -0,0 +1,150 @@
+use matrix::Vector;
+
+/// Sub-pixel precision of snapped vertex positions, in bits.
+pub const SUBPIXEL_BITS: u32 = 8;
+
+pub const SUBPIXELS: i64 = 1 << SUBPIXEL_BITS;
+
+/// Fixed-point edge function `a * x + b * y + c`, positive inside the triangle.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Edge {
+	pub a: i64,
+	pub b: i64,
+	pub c: i64,
+	pub bias: i64,
+}
+
+impl Edge {
+	fn new(p1: [i64; 2], p2: [i64; 2], p3: [i64; 2]) -> Self {
+		let a = p1[1] - p2[1];
+		let b = p2[0] - p1[0];
+		let c = p1[0] * p2[1] - p1[1] * p2[0];
+		let sign = (a * p3[0] + b * p3[1] + c).signum();
+		let (a, b, c) = (a * sign, b * sign, c * sign);
+		let top_left = a > 0 || (a == 0 && b > 0);
+
+		Self {
+			a,
+			b,
+			c,
+			bias: if top_left { 1 } else { 0 },
+		}
+	}
+
+	#[inline]
+	pub fn at(&self, x: i64, y: i64) -> i64 {
+		self.a * x + self.b * y + self.c
+	}
+
+	#[inline]
+	pub fn inside(&self, e: i64) -> bool {
+		e + self.bias > 0
+	}
+
+	#[inline]
+	pub fn range(&self, x0: i64, y0: i64, x1: i64, y1: i64) -> (i64, i64) {
+		let (min_x, max_x) = if self.a > 0 { (x0, x1) } else { (x1, x0) };
+		let (min_y, max_y) = if self.b > 0 { (y0, y1) } else { (y1, y0) };
+		(self.at(min_x, min_y), self.at(max_x, max_y))
+	}
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Coverage {
+	Outside,
+	Inside,
+	Partial,
+}
+
+#[inline]
+pub fn classify(edges: &[Edge; 3], x0: i64, y0: i64, x1: i64, y1: i64) -> Coverage {
+	let mut coverage = Coverage::Inside;
+
+	for edge in edges {
+		let (min, max) = edge.range(x0, y0, x1, y1);
+
+		if !edge.inside(max) {
+			return Coverage::Outside;
+		} else if !edge.inside(min) {
+			coverage = Coverage::Partial;
+		}
+	}
+
+	coverage
+}
+
+#[inline]
+pub fn snap(v: Vector<f32, 4>) -> [i64; 2] {
+	let scale = SUBPIXELS as f32 / v[3];
+	[(v[0] * scale).round() as i64, (v[1] * scale).round() as i64]
+}
+
+#[inline]
+pub fn center(x: usize) -> i64 {
+	x as i64 * SUBPIXELS + SUBPIXELS / 2
+}
+
+pub fn edges(vs: [Vector<f32, 4>; 3]) -> Option<[Edge; 3]> {
+	let [p1, p2, p3] = vs.map(snap);
+	let area = (p2[0] - p1[0]) * (p3[1] - p1[1]) - (p2[1] - p1[1]) * (p3[0] - p1[0]);
+
+	if area == 0 {
+		return None;
+	}
+
+	Some([
+		Edge::new(p2, p3, p1),
+		Edge::new(p3, p1, p2),
+		Edge::new(p1, p2, p3),
+	])
+}
+
+#[cfg(test)]
+mod tests {
+	use matrix::vector;
+
+	use super::*;
+
+	#[test]
+	fn shared_edge_test() {
+		let a = vector![0.0, 0.0, 0.0, 1.0];
+		let b = vector![4.0, 4.0, 0.0, 1.0];
+		let c = vector![4.0, 0.0, 0.0, 1.0];
+		let d = vector![0.0, 4.0, 0.0, 1.0];
+
+		let t1 = edges([a, b, c]).unwrap();
+		let t2 = edges([a, d, b]).unwrap();
+
+		// Pixel centers on the shared diagonal belong to exactly one of the two triangles.
+		for x in 0..4 {
+			let (px, py) = (center(x), center(x));
+			let in1 = t1.iter().all(|e| e.inside(e.at(px, py)));
+			let in2 = t2.iter().all(|e| e.inside(e.at(px, py)));
+			assert!(in1 != in2);
+		}
+	}
+
+	#[test]
+	fn classify_test() {
+		let a = vector![0.0, 0.0, 0.0, 1.0];
+		let b = vector![0.0, 64.0, 0.0, 1.0];
+		let c = vector![64.0, 0.0, 0.0, 1.0];
+		let edges = edges([a, b, c]).unwrap();
+		let block = |x: i64, y: i64| {
+			let (x0, y0) = (x * SUBPIXELS, y * SUBPIXELS);
+			classify(&edges, x0, y0, x0 + 8 * SUBPIXELS, y0 + 8 * SUBPIXELS)
+		};
+
+		assert_eq!(block(8, 8), Coverage::Inside);
+		assert_eq!(block(28, 28), Coverage::Partial);
+		assert_eq!(block(48, 48), Coverage::Outside);
+	}
+
+	#[test]
+	fn degenerate_test() {
+		let a = vector![0.0, 0.0, 0.0, 1.0];
+		let b = vector![1.0, 1.0, 0.0, 1.0];
+		let c = vector![2.0, 2.0, 0.0, 1.0];
+		assert_eq!(edges([a, b, c]), None);
+	}
+}
@@ -2,7 +2,10 @@
 
 pub mod bounds;
 pub mod buffer;
+pub mod clip;
+pub mod edge;
 pub mod light;
+pub mod pfm;
 pub mod pipeline;
 pub mod texture;
 pub mod varying;
@@ -8,17 +8,16 @@ pub struct Light {
 	pub position: Vector<f32, 3>,
 }
 
+/// Shades one fragment, returning its color and alpha, or `None` when it is fully transparent.
 #[inline]
 pub fn blinn_phong(
-	current: Array<f32, 3>,
 	position: Vector<f32, 3>,
 	normal: Vector<f32, 3>,
 	uv: Option<Vector<f32, 2>>,
 	camera: Vector<f32, 3>,
 	lights: &[Light],
 	material: &obj::Material,
-) -> Option<Array<f32, 3>> {
-	// TODO alpha
+) -> Option<(Array<f32, 3>, f32)> {
 	let alpha = material.alpha(uv);
 	if alpha == 0.0 {
 		return None;
@@ -39,6 +38,5 @@ pub fn blinn_phong(
 			+ specular_reflection * specular * light.specular_color
 	});
 
-	let color = color * alpha + current * (1.0 - alpha);
-	Some(color.clamp(0.0, 1.0))
+	Some((color, alpha))
 }
@@ -0,0 +1,28 @@
+use std::{
+	fs::File,
+	io::{self, BufWriter, Write},
+};
+
+pub fn write(
+	path: &str,
+	width: usize,
+	height: usize,
+	channels: usize,
+	data: &[f32],
+) -> io::Result<()> {
+	assert!(channels == 1 || channels == 3);
+	assert_eq!(data.len(), width * height * channels);
+
+	let mut writer = BufWriter::new(File::create(path)?);
+	let magic = if channels == 3 { "PF" } else { "Pf" };
+	write!(writer, "{}\n{} {}\n-1.0\n", magic, width, height)?;
+
+	// Rows are stored bottom to top; a negative scale means little-endian.
+	for row in data.chunks_exact(width * channels).rev() {
+		for value in row {
+			writer.write_all(&value.to_le_bytes())?;
+		}
+	}
+
+	writer.flush()
+}
@@ -110,6 +110,14 @@ pub fn perspective_near(ratio: f32, fov_y: f32, near: f32) -> Matrix<f32, 4, 4>
 	m
 }
 
+/// Infinite projection with reversed depth: `z / w` is 1 at `near` and falls towards 0 at infinity.
+pub fn perspective_reversed(ratio: f32, fov_y: f32, near: f32) -> Matrix<f32, 4, 4> {
+	let mut m = perspective(ratio, fov_y);
+	m[(2, 2)] = 0.0;
+	m[(3, 2)] = near;
+	m
+}
+
 pub fn perspective_near_far(ratio: f32, fov_y: f32, near: f32, far: f32) -> Matrix<f32, 4, 4> {
 	let mut m = perspective_near(ratio, fov_y, near);
 	m[(2, 2)] = (far + near) / (near - far);